@@ -3,12 +3,17 @@ use std::str::FromStr;
 use crate::math::Unit;
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MainAxisAlignment {
     Start,
     Center,
     End,
+    /// The free space is put between the children, none before the first and after the last one.
     SpaceBetween,
+    /// The free space is put around every child, so the outer gaps are half as wide as the inner ones.
+    SpaceAround,
+    /// The free space is split evenly between the children and before the first and after the last one.
+    SpaceEvenly,
 }
 
 impl Default for MainAxisAlignment {
@@ -26,6 +31,8 @@ impl FromStr for MainAxisAlignment {
             "center" => Self::Center,
             "end" => Self::End,
             "spaceBetween" => Self::SpaceBetween,
+            "spaceAround" => Self::SpaceAround,
+            "spaceEvenly" => Self::SpaceEvenly,
             _ => return Err(())
         };
         Ok(align)
@@ -33,35 +40,44 @@ impl FromStr for MainAxisAlignment {
 }
 
 impl MainAxisAlignment {
-    pub fn get_positions<T: Unit>(&self, max_space: T, spaces: &[T]) -> Vec<T> {
-        let needed_space = spaces.iter().fold(T::from(0.0), |v, space| v + *space);
+    /// Positions of children with the sizes `spaces`, which are at least `spacing` apart.
+    pub fn get_positions<T: Unit>(&self, max_space: T, spaces: &[T], spacing: T) -> Vec<T> {
+        let gaps = T::from(spaces.len().saturating_sub(1) as f64);
+        let needed_space = spaces.iter().fold(spacing * gaps, |v, space| v + *space);
         let remaining_space = max_space - needed_space;
 
-        let mut pos = match self {
-            MainAxisAlignment::Start => T::from(0.0),
-            MainAxisAlignment::Center => (remaining_space) / T::from(2.0),
-            MainAxisAlignment::End => remaining_space,
-            MainAxisAlignment::SpaceBetween => T::from(0.0),
-        };
-
-        let padding = match self {
-            MainAxisAlignment::SpaceBetween => remaining_space / ((spaces.len() - 1) as f64).into(),
-            _ => T::from(0.0),
+        // Free space is only distributed between the children if there is any
+        let free_space = T::from(Into::<f64>::into(remaining_space).max(0.0));
+        let count = T::from(spaces.len() as f64);
+
+        let (mut pos, padding) = match self {
+            MainAxisAlignment::Start => (T::from(0.0), T::from(0.0)),
+            MainAxisAlignment::Center => (remaining_space / T::from(2.0), T::from(0.0)),
+            MainAxisAlignment::End => (remaining_space, T::from(0.0)),
+            MainAxisAlignment::SpaceBetween if spaces.len() > 1 => (T::from(0.0), free_space / gaps),
+            MainAxisAlignment::SpaceBetween => (T::from(0.0), T::from(0.0)),
+            MainAxisAlignment::SpaceAround => {
+                let padding = free_space / count;
+                (padding / T::from(2.0), padding)
+            }
+            MainAxisAlignment::SpaceEvenly => {
+                let padding = free_space / (count + T::from(1.0));
+                (padding, padding)
+            }
         };
 
         let mut positions = Vec::new();
 
         for space in spaces {
             positions.push(pos);
-            pos += *space + padding;
+            pos += *space + spacing + padding;
         }
-        
 
         positions
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum CrossAxisAlignment {
     Start,
     Center,
@@ -108,4 +124,51 @@ impl CrossAxisAlignment {
             CrossAxisAlignment::Stretch => max_space,
         }
     }
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum TextAlign {
+    Left,
+    Right,
+    Center,
+    /// Every line fills the whole width, including the last one.
+    Justify,
+    /// Every line but the last fills the whole width, the last one is aligned left.
+    #[default]
+    JustifyAllButLast,
+}
+
+impl FromStr for TextAlign {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let align = match s {
+            "left" => Self::Left,
+            "right" => Self::Right,
+            "center" => Self::Center,
+            "justify" => Self::Justify,
+            "justifyAllButLast" => Self::JustifyAllButLast,
+            _ => return Err(()),
+        };
+        Ok(align)
+    }
+}
+
+impl TextAlign {
+    pub fn is_justified(&self, is_last_line: bool) -> bool {
+        match self {
+            TextAlign::Justify => true,
+            TextAlign::JustifyAllButLast => !is_last_line,
+            _ => false,
+        }
+    }
+
+    /// The offset of a line that is not justified.
+    pub fn get_offset<T: Unit>(&self, max_space: T, space: T) -> T {
+        match self {
+            TextAlign::Right => max_space - space,
+            TextAlign::Center => (max_space - space) / T::from(2.0),
+            _ => T::from(0.0),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::ops::{Add, Mul, Neg, Sub};
+
+use super::{Cm, Em, In, Mm, Percent, Pt};
+
+/// A length that may depend on the font size and on the size of its container.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Length {
+    pub abs: Pt,
+    pub em: Em,
+    pub percent: Percent,
+}
+
+impl Length {
+    pub const ZERO: Self = Self {
+        abs: Pt(0.0),
+        em: Em(0.0),
+        percent: Percent(0.0),
+    };
+
+    /// Creates a length from a value and the name of its unit, like `pt` or `%`.
+    pub fn with_unit(value: f64, unit: &str) -> Option<Self> {
+        let length = match unit {
+            "pt" => Pt(value).into(),
+            "mm" => Mm(value).into(),
+            "cm" => Cm(value).into(),
+            "in" => In(value).into(),
+            "em" => Em(value).into(),
+            "%" => Percent(value).into(),
+            _ => return None,
+        };
+
+        Some(length)
+    }
+
+    /// Whether the length does not depend on the font size or the container.
+    pub fn is_absolute(&self) -> bool {
+        self.em == Em(0.0) && self.percent == Percent(0.0)
+    }
+
+    /// Converts the length to points, percentages are relative to `container`.
+    pub fn resolve(&self, font_size: Pt, container: Pt) -> Pt {
+        self.abs + font_size * self.em.0 + container * (self.percent.0 / 100.0)
+    }
+}
+
+impl From<Pt> for Length {
+    fn from(value: Pt) -> Self {
+        Self {
+            abs: value,
+            ..Self::ZERO
+        }
+    }
+}
+
+impl From<Mm> for Length {
+    fn from(value: Mm) -> Self {
+        Pt::from(value).into()
+    }
+}
+
+impl From<Cm> for Length {
+    fn from(value: Cm) -> Self {
+        Pt::from(value).into()
+    }
+}
+
+impl From<In> for Length {
+    fn from(value: In) -> Self {
+        Pt::from(value).into()
+    }
+}
+
+impl From<Em> for Length {
+    fn from(value: Em) -> Self {
+        Self {
+            em: value,
+            ..Self::ZERO
+        }
+    }
+}
+
+impl From<Percent> for Length {
+    fn from(value: Percent) -> Self {
+        Self {
+            percent: value,
+            ..Self::ZERO
+        }
+    }
+}
+
+impl Add for Length {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            abs: self.abs + rhs.abs,
+            em: self.em + rhs.em,
+            percent: self.percent + rhs.percent,
+        }
+    }
+}
+
+impl Sub for Length {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl Neg for Length {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            abs: -self.abs,
+            em: -self.em,
+            percent: -self.percent,
+        }
+    }
+}
+
+impl Mul<f64> for Length {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self {
+            abs: self.abs * rhs,
+            em: self.em * rhs,
+            percent: self.percent * rhs,
+        }
+    }
+}
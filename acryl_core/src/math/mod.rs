@@ -3,9 +3,11 @@ mod coords;
 mod vector;
 mod matrix;
 mod area;
+mod length;
 
 pub use unit::*;
 pub use coords::*;
 pub use vector::*;
 pub use matrix::*;
-pub use area::*;
\ No newline at end of file
+pub use area::*;
+pub use length::*;
\ No newline at end of file
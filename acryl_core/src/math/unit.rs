@@ -55,6 +55,16 @@ impl VectorComponent for $name {
 }
 impl Unit for $name {}
 
+impl $name {
+    pub fn min(self, other: Self) -> Self {
+        Self(self.0.min(other.0))
+    }
+
+    pub fn max(self, other: Self) -> Self {
+        Self(self.0.max(other.0))
+    }
+}
+
 // Math operations
 
 impl Add for $name {
@@ -145,14 +155,27 @@ impl From<$name> for $converted {
 unit!(Pt, "pt",
     0.3527777778 => Mm,
     0.0352777778 => Cm,
+    0.0138888889 => In,
 );
 
 unit!(Mm, "mm",
     2.8346456693 => Pt,
     0.1000000000 => Cm,
+    0.0393700787 => In,
 );
 
 unit!(Cm, "cm",
     10.000000000 => Mm,
     28.346456693 => Pt,
-);
\ No newline at end of file
+    0.3937007874 => In,
+);
+
+unit!(In, "in",
+    72.000000000 => Pt,
+    25.400000000 => Mm,
+    2.5400000000 => Cm,
+);
+
+// Relative units, which are only converted once the font size or the size of the container is known
+unit!(Em, "em",);
+unit!(Percent, "%",);
\ No newline at end of file
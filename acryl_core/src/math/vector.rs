@@ -144,6 +144,12 @@ impl<T: VectorComponent, C: Coords> Debug for $name<T, C> {
     }
 }
 
+impl<T: VectorComponent, C: Coords> PartialEq for $name<T, C> {
+    fn eq(&self, other: &Self) -> bool {
+        $(self.$val == other.$val)&&*
+    }
+}
+
 op_impl!($name, (+ Add add), (+= AddAssign add_assign), $($val,)*);
 op_impl!($name, (- Sub sub), (-= SubAssign sub_assign), $($val,)*);
 op_impl!($name, (* Mul mul), (*= MulAssign mul_assign), $($val,)*);
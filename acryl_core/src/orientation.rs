@@ -1,6 +1,6 @@
 use crate::math::{VectorComponent, Vector2};
 
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum Orientation {
     #[default]
     Vertical,
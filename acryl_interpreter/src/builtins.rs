@@ -0,0 +1,537 @@
+use acryl_core::{
+    math::{Em, Length, Pt},
+    CrossAxisAlignment, MainAxisAlignment, Orientation, TextAlign,
+};
+use acryl_layout::{
+    flex_style::{FlexFit, FlexStyle},
+    node::Node,
+    padding_values::PaddingValues,
+    page_template::Heading,
+    paragraph::TextStyle,
+};
+use acryl_pdf::stream::{FillPaintArgs, FillRule, LineCap, LineJoin, StrokePaintArgs};
+
+use crate::{interpreter::Region, EvalError, FnCall, Interpreter, Value};
+
+pub(crate) type Builtin =
+    for<'a> fn(&mut Interpreter<'a>, &FnCall<'a, 'a>) -> Result<Vec<Node>, EvalError>;
+
+/// A builtin that results in text, which becomes part of the surrounding paragraph.
+pub(crate) type InlineBuiltin =
+    for<'a> fn(&mut Interpreter<'a>, &FnCall<'a, 'a>) -> Result<String, EvalError>;
+
+/// A builtin that results in the text style of its content, which continues the surrounding
+/// paragraph.
+pub(crate) type StyleBuiltin =
+    for<'a> fn(&Interpreter<'a>, &FnCall<'a, 'a>) -> Result<TextStyle, EvalError>;
+
+/// The text of a reference to a label that is not known yet.
+const UNRESOLVED: &str = "??";
+
+/// The deepest level of `\heading`, section numbers do not get longer than this.
+const MAX_HEADING_LEVEL: i64 = 6;
+
+pub(crate) fn get_builtin(name: &str) -> Option<Builtin> {
+    let builtin: Builtin = match name {
+        "align" => align,
+        "box" => r#box,
+        "column" => column,
+        "expanded" => expanded,
+        "flexible" => flexible,
+        "footer" => footer,
+        "header" => header,
+        "heading" => heading,
+        "mark" => mark,
+        "padding" => padding,
+        "pageBreak" => page_break,
+        "row" => row,
+        "size" => size,
+        "tableOfContents" => table_of_contents,
+        _ => return None,
+    };
+
+    Some(builtin)
+}
+
+pub(crate) fn get_style_builtin(name: &str) -> Option<StyleBuiltin> {
+    let builtin: StyleBuiltin = match name {
+        "fontSize" => font_size,
+        "set" => set,
+        _ => return None,
+    };
+
+    Some(builtin)
+}
+
+pub(crate) fn get_inline_builtin(name: &str) -> Option<InlineBuiltin> {
+    let builtin: InlineBuiltin = match name {
+        "currentMark" => current_mark,
+        "pageCount" => page_count,
+        "pageNumber" => page_number,
+        "pageref" => page_reference,
+        "ref" => reference,
+        _ => return None,
+    };
+
+    Some(builtin)
+}
+
+fn align<'a>(
+    interpreter: &mut Interpreter<'a>,
+    call: &FnCall<'a, 'a>,
+) -> Result<Vec<Node>, EvalError> {
+    let align: TextAlign = call
+        .ident(0, "align")?
+        .parse()
+        .map_err(|_| call.error("unknown text alignment"))?;
+
+    let outer = interpreter.style.align;
+    interpreter.style.align = align;
+
+    let nodes = interpreter.eval_content(call.content);
+
+    interpreter.style.align = outer;
+
+    Ok(nodes)
+}
+
+fn font_size<'a>(interpreter: &Interpreter<'a>, call: &FnCall<'a, 'a>) -> Result<TextStyle, EvalError> {
+    let size = absolute_length(interpreter, call, call.length(0, "size")?)?;
+
+    if size.0 <= 0.0 {
+        return Err(call.error("'size' of '\\fontSize' needs to be positive"));
+    }
+
+    Ok(TextStyle {
+        font_size: size.0,
+        ..interpreter.style.text.clone()
+    })
+}
+
+/// Changes the style of its content, only named arguments are taken so that any of them can be
+/// left out.
+fn set<'a>(interpreter: &Interpreter<'a>, call: &FnCall<'a, 'a>) -> Result<TextStyle, EvalError> {
+    let (names, unnamed) = call.arguments.names();
+
+    if unnamed > 0 {
+        return Err(call.error(format!("'\\{}' only takes named arguments", call.name)));
+    }
+
+    let mut style = interpreter.style.text.clone();
+
+    for (name, span) in names {
+        match name {
+            "font" => style.font = interpreter.font(&call.str(0, name)?, span)?,
+            "fontSize" => {
+                style.font_size = absolute_length(interpreter, call, call.length(0, name)?)?.0;
+
+                if style.font_size <= 0.0 {
+                    return Err(EvalError::new(
+                        span,
+                        format!("'fontSize' of '\\{}' needs to be positive", call.name),
+                    ));
+                }
+            }
+            "color" => style.color = Some(call.color(0, name)?),
+            _ => {
+                return Err(EvalError::new(
+                    span,
+                    format!("'\\{}' has no parameter '{}'", call.name, name),
+                ))
+            }
+        }
+    }
+
+    Ok(style)
+}
+
+/// A heading of a section at `level`, which starts at 1.
+///
+/// Numbered headings count their section, the number is put before their content and references
+/// to the labels in the section. The heading is listed in the table of contents with the text of
+/// its content.
+fn heading<'a>(
+    interpreter: &mut Interpreter<'a>,
+    call: &FnCall<'a, 'a>,
+) -> Result<Vec<Node>, EvalError> {
+    let level = call.int(0, "level")?;
+
+    if level < 1 {
+        return Err(call.error(format!("'level' of '\\{}' needs to be positive", call.name)));
+    }
+
+    if level > MAX_HEADING_LEVEL {
+        return Err(call.error(format!(
+            "'level' of '\\{}' can be at most {}, got {}",
+            call.name, MAX_HEADING_LEVEL, level
+        )));
+    }
+
+    let level = level as usize;
+
+    let number = if call.bool_or(1, "numbered", true)? {
+        // Deeper sections start over
+        interpreter.sections.resize(level, 0);
+        interpreter.sections[level - 1] += 1;
+        interpreter.section()
+    } else {
+        None
+    };
+
+    interpreter.prefix = number.clone();
+    let mut nodes = interpreter.eval_content(call.content);
+
+    // The number is not part of the title, if it was put before a word
+    let prefixed = interpreter.prefix.take().is_none();
+    let text = nodes
+        .iter()
+        .map(Node::plain_text)
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let title = match &number {
+        Some(number) if prefixed => text
+            .strip_prefix(number.as_str())
+            .unwrap_or(&text)
+            .trim_start()
+            .to_owned(),
+        _ => text,
+    };
+
+    let heading = Heading {
+        level,
+        number,
+        title,
+        label: interpreter.key.take().map(str::to_owned),
+        page: None,
+    };
+
+    nodes.insert(0, Node::heading(heading));
+
+    Ok(nodes)
+}
+
+/// Lists the headings up to `depth` with their pages, entries are indented by `indent` for every
+/// level.
+///
+/// The headings are the ones of the previous pass, so the table is empty in the first one.
+fn table_of_contents<'a>(
+    interpreter: &mut Interpreter<'a>,
+    call: &FnCall<'a, 'a>,
+) -> Result<Vec<Node>, EvalError> {
+    let depth = call.int_or(0, "depth", 3)?;
+    let indent = absolute_length(
+        interpreter,
+        call,
+        call.length_or(1, "indent", Em(1.5).into())?,
+    )?;
+
+    let entries = interpreter
+        .headings
+        .iter()
+        .filter(|heading| heading.level as i64 <= depth)
+        .cloned()
+        .collect();
+
+    Ok(vec![Node::toc(entries, interpreter.style.clone(), indent)])
+}
+
+fn column<'a>(
+    interpreter: &mut Interpreter<'a>,
+    call: &FnCall<'a, 'a>,
+) -> Result<Vec<Node>, EvalError> {
+    flex(interpreter, call, Orientation::Vertical)
+}
+
+fn row<'a>(
+    interpreter: &mut Interpreter<'a>,
+    call: &FnCall<'a, 'a>,
+) -> Result<Vec<Node>, EvalError> {
+    flex(interpreter, call, Orientation::Horizontal)
+}
+
+fn flex<'a>(
+    interpreter: &mut Interpreter<'a>,
+    call: &FnCall<'a, 'a>,
+    orientation: Orientation,
+) -> Result<Vec<Node>, EvalError> {
+    let spacing = call.length_or(0, "spacing", Length::ZERO)?;
+
+    let main_axis_alignment: MainAxisAlignment = call
+        .ident_or(1, "mainAxis", "start")?
+        .parse()
+        .map_err(|_| call.error("unknown main axis alignment"))?;
+
+    let cross_axis_alignment: CrossAxisAlignment = call
+        .ident_or(2, "crossAxis", "start")?
+        .parse()
+        .map_err(|_| call.error("unknown cross axis alignment"))?;
+
+    let style = FlexStyle {
+        spacing,
+        main_axis_alignment,
+        cross_axis_alignment,
+    };
+
+    let children = interpreter.eval_content(call.content);
+
+    Ok(vec![Node::flex(orientation, children, style)])
+}
+
+fn expanded<'a>(
+    interpreter: &mut Interpreter<'a>,
+    call: &FnCall<'a, 'a>,
+) -> Result<Vec<Node>, EvalError> {
+    flex_child(interpreter, call, FlexFit::Tight)
+}
+
+fn flexible<'a>(
+    interpreter: &mut Interpreter<'a>,
+    call: &FnCall<'a, 'a>,
+) -> Result<Vec<Node>, EvalError> {
+    flex_child(interpreter, call, FlexFit::Loose)
+}
+
+/// A flexible child of a flex node, which is empty space if it has no content.
+fn flex_child<'a>(
+    interpreter: &mut Interpreter<'a>,
+    call: &FnCall<'a, 'a>,
+    fit: FlexFit,
+) -> Result<Vec<Node>, EvalError> {
+    let flex = call.number_or(0, "flex", 1.0)?;
+
+    if flex <= 0.0 {
+        return Err(call.error(format!("'flex' of '\\{}' needs to be positive", call.name)));
+    }
+
+    let node = if call.content.is_empty() {
+        Node::spacer(flex)
+    } else {
+        interpreter.eval_single(call)?.flexible(flex, fit)
+    };
+
+    Ok(vec![node])
+}
+
+fn header<'a>(
+    interpreter: &mut Interpreter<'a>,
+    call: &FnCall<'a, 'a>,
+) -> Result<Vec<Node>, EvalError> {
+    interpreter.header = Some(page_region(interpreter, call)?);
+    Ok(Vec::new())
+}
+
+fn footer<'a>(
+    interpreter: &mut Interpreter<'a>,
+    call: &FnCall<'a, 'a>,
+) -> Result<Vec<Node>, EvalError> {
+    interpreter.footer = Some(page_region(interpreter, call)?);
+    Ok(Vec::new())
+}
+
+/// The content is evaluated again for every page, with the style at the point of definition.
+///
+/// Without a `height` the region is measured on the first page, which is too small if its content
+/// depends on marks. Headers and footers are measured before the body, so their lengths can not be
+/// relative to it.
+fn page_region<'a>(
+    interpreter: &Interpreter<'a>,
+    call: &FnCall<'a, 'a>,
+) -> Result<Region<'a>, EvalError> {
+    if interpreter.page.is_some() {
+        return Err(call.error(format!(
+            "'\\{}' can not be used in a header or footer",
+            call.name
+        )));
+    }
+
+    Ok(Region {
+        content: call.content,
+        style: interpreter.style.clone(),
+        scope: interpreter.scope.clone(),
+        includes: interpreter.includes.clone(),
+        fonts: interpreter.fonts.clone(),
+        labels: interpreter.labels.clone(),
+        height: match call.arguments.get(1, "height") {
+            Some(_) => Some(absolute_length(
+                interpreter,
+                call,
+                call.length(1, "height")?,
+            )?),
+            None => None,
+        },
+        spacing: absolute_length(
+            interpreter,
+            call,
+            call.length_or(0, "spacing", Region::DEFAULT_SPACING.into())?,
+        )?,
+    })
+}
+
+/// Resolves a length that is needed before the layout, so it can not be relative to a container.
+fn absolute_length(
+    interpreter: &Interpreter,
+    call: &FnCall,
+    length: Length,
+) -> Result<Pt, EvalError> {
+    if length.percent.0 != 0.0 {
+        return Err(call.error(format!(
+            "lengths of '\\{}' can not be percentages",
+            call.name
+        )));
+    }
+
+    Ok(length.resolve(Pt(interpreter.style.text.font_size), Pt(0.0)))
+}
+
+/// Fills and strokes the area of its content, strokes are drawn on the border of the area.
+fn r#box<'a>(
+    interpreter: &mut Interpreter<'a>,
+    call: &FnCall<'a, 'a>,
+) -> Result<Vec<Node>, EvalError> {
+    let fill = match call.arguments.get(0, "fill") {
+        Some(_) => Some(FillPaintArgs {
+            color: call.color(0, "fill")?,
+            fill_rule: FillRule::NonzeroWinding,
+        }),
+        None => None,
+    };
+
+    let stroke = match call.arguments.get(1, "stroke") {
+        Some(_) => Some(StrokePaintArgs {
+            close: true,
+            color: call.color(1, "stroke")?,
+            line_width: absolute_length(
+                interpreter,
+                call,
+                call.length_or(2, "strokeWidth", Pt(1.0).into())?,
+            )?,
+            line_cap: LineCap::default(),
+            line_join: LineJoin::default(),
+            miter_limit: Pt(10.0),
+            dash_pattern: (dash_pattern(interpreter, call)?, 0),
+        }),
+        None => None,
+    };
+
+    let child = interpreter.eval_single(call)?;
+
+    Ok(vec![child.with_color_box(fill, stroke)])
+}
+
+/// The lengths of the dashes and gaps of a stroke, an empty pattern draws a solid line.
+fn dash_pattern(interpreter: &Interpreter, call: &FnCall) -> Result<Vec<Pt>, EvalError> {
+    let Some(argument) = call.arguments.get(3, "dash") else {
+        return Ok(Vec::new());
+    };
+
+    call.list(3, "dash")?
+        .into_iter()
+        .map(|value| {
+            let length = match value {
+                Value::Length(length) => length,
+                value => Pt(value.as_number().ok_or_else(|| {
+                    EvalError::new(argument.1, "'dash' needs to be a list of lengths")
+                })?)
+                .into(),
+            };
+
+            absolute_length(interpreter, call, length)
+        })
+        .collect()
+}
+
+fn mark<'a>(
+    _interpreter: &mut Interpreter<'a>,
+    call: &FnCall<'a, 'a>,
+) -> Result<Vec<Node>, EvalError> {
+    let name = call.ident(0, "name")?;
+    let value = call.str(1, "value")?;
+
+    Ok(vec![Node::mark(name, value)])
+}
+
+fn padding<'a>(
+    interpreter: &mut Interpreter<'a>,
+    call: &FnCall<'a, 'a>,
+) -> Result<Vec<Node>, EvalError> {
+    let amount = call.length(0, "all")?;
+    let child = interpreter.eval_single(call)?;
+
+    Ok(vec![child.with_padding(PaddingValues::all(amount))])
+}
+
+fn page_break<'a>(
+    _interpreter: &mut Interpreter<'a>,
+    _call: &FnCall<'a, 'a>,
+) -> Result<Vec<Node>, EvalError> {
+    Ok(vec![Node::page_break()])
+}
+
+fn size<'a>(
+    interpreter: &mut Interpreter<'a>,
+    call: &FnCall<'a, 'a>,
+) -> Result<Vec<Node>, EvalError> {
+    let width = call.length(0, "width")?;
+    let height = call.length(1, "height")?;
+
+    let node = if call.content.is_empty() {
+        Node::size(width, height)
+    } else {
+        interpreter.eval_single(call)?.with_size(width, height)
+    };
+
+    Ok(vec![node])
+}
+
+fn current_mark<'a>(
+    interpreter: &mut Interpreter<'a>,
+    call: &FnCall<'a, 'a>,
+) -> Result<String, EvalError> {
+    let name = call.ident(0, "name")?;
+    let page = interpreter.page(call)?;
+
+    Ok(page.mark(&name).unwrap_or_default().to_owned())
+}
+
+fn page_count<'a>(
+    interpreter: &mut Interpreter<'a>,
+    call: &FnCall<'a, 'a>,
+) -> Result<String, EvalError> {
+    Ok(interpreter.page(call)?.count.to_string())
+}
+
+fn page_number<'a>(
+    interpreter: &mut Interpreter<'a>,
+    call: &FnCall<'a, 'a>,
+) -> Result<String, EvalError> {
+    Ok(interpreter.page(call)?.number.to_string())
+}
+
+/// The number of the section a label is in.
+fn reference<'a>(
+    interpreter: &mut Interpreter<'a>,
+    call: &FnCall<'a, 'a>,
+) -> Result<String, EvalError> {
+    let Some(label) = interpreter.label(call)? else {
+        return Ok(UNRESOLVED.to_owned());
+    };
+
+    label.section.ok_or_else(|| {
+        call.error(format!(
+            "'\\{}' needs a label in a numbered section, use '\\pageref' for its page",
+            call.name
+        ))
+    })
+}
+
+/// The number of the page a label is on.
+fn page_reference<'a>(
+    interpreter: &mut Interpreter<'a>,
+    call: &FnCall<'a, 'a>,
+) -> Result<String, EvalError> {
+    Ok(interpreter
+        .label(call)?
+        .and_then(|label| label.page)
+        .map_or_else(|| UNRESOLVED.to_owned(), |page| page.to_string()))
+}
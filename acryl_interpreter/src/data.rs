@@ -0,0 +1,316 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use acryl_parser::{source_map::SourceMap, Span};
+
+use crate::Value;
+
+/// The formats data files can be loaded from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataFormat {
+    Json,
+    Csv,
+    Toml,
+}
+
+impl DataFormat {
+    /// The format of a file, by its extension.
+    pub fn of(path: &Path) -> Option<Self> {
+        path.extension()?
+            .to_str()?
+            .to_ascii_lowercase()
+            .parse()
+            .ok()
+    }
+
+    /// Reads and parses the file at `path`, relative paths start in the working directory.
+    pub fn load<P: AsRef<Path>>(self, path: P) -> Result<Value, String> {
+        let path = path.as_ref();
+
+        let source = fs::read_to_string(path)
+            .map_err(|error| format!("could not read '{}': {}", path.display(), error))?;
+
+        self.parse(&source)
+            .map_err(|error| format!("invalid {} in '{}': {}", self, path.display(), error))
+    }
+
+    pub fn parse(self, source: &str) -> Result<Value, String> {
+        match self {
+            Self::Json => serde_json::from_str(source)
+                .map(from_json)
+                .map_err(|error| error.to_string()),
+            Self::Csv => parse_csv(source),
+            Self::Toml => toml::from_str(source)
+                .map(|table| from_toml(toml::Value::Table(table)))
+                .map_err(|error| error.message().to_owned()),
+        }
+    }
+}
+
+/// The data files that documents load with `json`, `csv` and `toml`.
+///
+/// Paths are relative to the file of the call, and every file is only read once, even if the
+/// document is evaluated again to resolve its references.
+#[derive(Debug, Default)]
+pub struct DataFiles {
+    /// The directories of the source files, by the offset the file starts at.
+    dirs: BTreeMap<usize, PathBuf>,
+    values: RefCell<BTreeMap<PathBuf, Value>>,
+}
+
+impl DataFiles {
+    pub fn new(sources: &SourceMap) -> Self {
+        let dirs = sources
+            .files()
+            .map(|file| {
+                let dir = Path::new(file.name()).parent().unwrap_or(Path::new(""));
+                (file.start(), dir.to_owned())
+            })
+            .collect();
+
+        Self {
+            dirs,
+            values: RefCell::default(),
+        }
+    }
+
+    /// Loads the file at `path`, relative to the directory of the file `span` points into.
+    pub(crate) fn load(&self, format: DataFormat, path: &str, span: Span) -> Result<Value, String> {
+        let path = match self.dirs.range(..=span.start).next_back() {
+            Some((_, dir)) => dir.join(path),
+            None => PathBuf::from(path),
+        };
+
+        if let Some(value) = self.values.borrow().get(&path) {
+            return Ok(value.clone());
+        }
+
+        let value = format.load(&path)?;
+        self.values.borrow_mut().insert(path, value.clone());
+
+        Ok(value)
+    }
+}
+
+impl FromStr for DataFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            "toml" => Ok(Self::Toml),
+            _ => Err(()),
+        }
+    }
+}
+
+impl std::fmt::Display for DataFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Json => "JSON",
+            Self::Csv => "CSV",
+            Self::Toml => "TOML",
+        })
+    }
+}
+
+fn from_json(value: serde_json::Value) -> Value {
+    match value {
+        serde_json::Value::Null => Value::None,
+        serde_json::Value::Bool(value) => Value::Bool(value),
+        serde_json::Value::Number(number) => match number.as_i64() {
+            Some(value) => Value::Int(value),
+            None => Value::Float(number.as_f64().unwrap_or(f64::NAN)),
+        },
+        serde_json::Value::String(value) => Value::Str(value),
+        serde_json::Value::Array(items) => Value::List(items.into_iter().map(from_json).collect()),
+        serde_json::Value::Object(entries) => Value::Dict(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key, from_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Dates and times have no value of their own, they are kept as they are written.
+fn from_toml(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(value) => Value::Str(value),
+        toml::Value::Integer(value) => Value::Int(value),
+        toml::Value::Float(value) => Value::Float(value),
+        toml::Value::Boolean(value) => Value::Bool(value),
+        toml::Value::Datetime(value) => Value::Str(value.to_string()),
+        toml::Value::Array(items) => Value::List(items.into_iter().map(from_toml).collect()),
+        toml::Value::Table(entries) => Value::Dict(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key, from_toml(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// A list with a dict for every row, the first row names the columns.
+///
+/// Cells that are numbers become ints or floats, so they can be calculated with.
+fn parse_csv(source: &str) -> Result<Value, String> {
+    let mut reader = csv::Reader::from_reader(source.as_bytes());
+
+    let columns = reader
+        .headers()
+        .map_err(|error| error.to_string())?
+        .iter()
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+
+    let rows = reader
+        .records()
+        .map(|record| {
+            let record = record.map_err(|error| error.to_string())?;
+
+            Ok(Value::Dict(
+                columns
+                    .iter()
+                    .cloned()
+                    .zip(record.iter().map(csv_cell))
+                    .collect(),
+            ))
+        })
+        .collect::<Result<_, String>>()?;
+
+    Ok(Value::List(rows))
+}
+
+fn csv_cell(cell: &str) -> Value {
+    // Words like `inf` are parsed as floats too
+    let is_number = cell.bytes().any(|byte| byte.is_ascii_digit());
+
+    if let Ok(value) = cell.parse() {
+        Value::Int(value)
+    } else if let (true, Ok(value)) = (is_number, cell.parse()) {
+        Value::Float(value)
+    } else {
+        Value::Str(cell.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, env, fs, path::Path, process};
+
+    use acryl_parser::{source_map::SourceMap, Span};
+
+    use crate::Value;
+
+    use super::{DataFiles, DataFormat};
+
+    fn dict(entries: &[(&str, Value)]) -> Value {
+        Value::Dict(
+            entries
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect::<BTreeMap<_, _>>(),
+        )
+    }
+
+    fn str(value: &str) -> Value {
+        Value::Str(value.to_owned())
+    }
+
+    #[test]
+    fn formats_are_found_by_the_extension() {
+        assert_eq!(DataFormat::of(Path::new("a/b.json")), Some(DataFormat::Json));
+        assert_eq!(DataFormat::of(Path::new("b.CSV")), Some(DataFormat::Csv));
+        assert_eq!(DataFormat::of(Path::new("b.toml")), Some(DataFormat::Toml));
+        assert_eq!(DataFormat::of(Path::new("b.txt")), None);
+        assert_eq!(DataFormat::of(Path::new("json")), None);
+    }
+
+    #[test]
+    fn json_is_parsed_into_values() {
+        assert_eq!(
+            DataFormat::Json.parse(r#"{ "a": [1, 2.5, "x", true, null], "b": {} }"#),
+            Ok(dict(&[
+                (
+                    "a",
+                    Value::List(vec![
+                        Value::Int(1),
+                        Value::Float(2.5),
+                        str("x"),
+                        Value::Bool(true),
+                        Value::None,
+                    ])
+                ),
+                ("b", dict(&[])),
+            ]))
+        );
+        assert!(DataFormat::Json.parse("{ \"a\": }").is_err());
+    }
+
+    #[test]
+    fn csv_rows_are_dicts_by_the_columns() {
+        assert_eq!(
+            DataFormat::Csv.parse("name,count,price\nApple,3,1.5\n\"Pear, green\",inf,-2\n"),
+            Ok(Value::List(vec![
+                dict(&[
+                    ("name", str("Apple")),
+                    ("count", Value::Int(3)),
+                    ("price", Value::Float(1.5)),
+                ]),
+                dict(&[
+                    ("name", str("Pear, green")),
+                    ("count", str("inf")),
+                    ("price", Value::Int(-2)),
+                ]),
+            ]))
+        );
+        assert!(DataFormat::Csv.parse("a,b\n1,2,3\n").is_err());
+    }
+
+    #[test]
+    fn toml_is_parsed_into_values() {
+        assert_eq!(
+            DataFormat::Toml.parse("title = \"Report\"\ndate = 2024-01-31\n[totals]\nsum = 12\n"),
+            Ok(dict(&[
+                ("title", str("Report")),
+                ("date", str("2024-01-31")),
+                ("totals", dict(&[("sum", Value::Int(12))])),
+            ]))
+        );
+        assert!(DataFormat::Toml.parse("title = ").is_err());
+    }
+
+    #[test]
+    fn paths_are_relative_to_the_calling_file_and_files_are_read_once() {
+        let dir = env::temp_dir().join(format!("acryl-data-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("values.json"), "1").unwrap();
+        fs::write(dir.join("sub/values.json"), "2").unwrap();
+
+        let mut sources = SourceMap::new();
+        let main = sources.add(dir.join("doc.acryl").display().to_string(), "main".into());
+        let sub = sources.add(dir.join("sub/part.acryl").display().to_string(), "sub".into());
+        let data_files = DataFiles::new(&sources);
+
+        let span = |file| Span::from(sources.get(file).start()..sources.get(file).end());
+        let load = |file| data_files.load(DataFormat::Json, "values.json", span(file));
+
+        assert_eq!(load(main), Ok(Value::Int(1)));
+        assert_eq!(load(sub), Ok(Value::Int(2)));
+
+        // Later passes of the document get the value that was read first
+        fs::write(dir.join("values.json"), "3").unwrap();
+        assert_eq!(load(main), Ok(Value::Int(1)));
+
+        let missing = data_files.load(DataFormat::Json, "missing.json", span(main));
+        assert!(missing.unwrap_err().starts_with("could not read"));
+    }
+}
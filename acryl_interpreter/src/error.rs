@@ -0,0 +1,30 @@
+use std::fmt::Display;
+
+use acryl_parser::{diagnostic::Diagnostic, Span};
+
+#[derive(Debug)]
+pub struct EvalError {
+    pub span: Span,
+    pub message: String,
+}
+
+impl EvalError {
+    pub fn new<M: Into<String>>(span: Span, message: M) -> Self {
+        Self {
+            span,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for EvalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl From<EvalError> for Diagnostic {
+    fn from(value: EvalError) -> Self {
+        Diagnostic::error(value.span, value.message)
+    }
+}
@@ -0,0 +1,364 @@
+use std::{cmp::Ordering, collections::BTreeMap};
+
+use acryl_core::math::Length;
+use acryl_parser::{
+    ast::{CodeToken, Expr, Op},
+    Span, Spanned,
+};
+
+use crate::{functions::call_function, scope::Scope, EvalError, Value};
+
+/// Evaluates `expr`, identifiers that are bound in `scope` are replaced by their value.
+///
+/// Identifiers that are not bound are an error, use [`eval_symbol`] where they name something.
+pub(crate) fn eval_expr(expr: &Spanned<Expr>, scope: &Scope) -> Result<Value, EvalError> {
+    let (expr, span) = expr;
+
+    match expr {
+        Expr::Token(token) => eval_token(token, scope, *span),
+        Expr::Unary(op, operand) => {
+            let operand = eval_expr(operand, scope)?;
+            unary(op, operand).map_err(|message| EvalError::new(*span, message))
+        }
+        // The right side is only evaluated if it decides the result
+        Expr::Binary(lhs, op, rhs) if *op == Op::AND || *op == Op::OR => {
+            let lhs_value = eval_bool(lhs, op, scope)?;
+
+            if lhs_value == (*op == Op::OR) {
+                Ok(Value::Bool(lhs_value))
+            } else {
+                eval_bool(rhs, op, scope).map(Value::Bool)
+            }
+        }
+        Expr::Binary(lhs, op, rhs) => {
+            let lhs = eval_expr(lhs, scope)?;
+            let rhs = eval_expr(rhs, scope)?;
+            binary(lhs, op, rhs).map_err(|message| EvalError::new(*span, message))
+        }
+        Expr::Call(name, arguments) => {
+            let arguments = arguments
+                .iter()
+                .map(|argument| Ok((eval_expr(argument, scope)?, argument.1)))
+                .collect::<Result<Vec<_>, EvalError>>()?;
+
+            call_function(name, arguments, *span, scope)
+        }
+        Expr::Field(value, name) => match eval_expr(value, scope)? {
+            Value::Dict(mut entries) => entries
+                .remove(*name)
+                .ok_or_else(|| EvalError::new(*span, format!("dict has no field '{}'", name))),
+            value => Err(EvalError::new(
+                *span,
+                format!("{} has no field '{}'", value.type_name(), name),
+            )),
+        },
+    }
+}
+
+/// Evaluates `expr` where identifiers name something, like a color or an alignment: an identifier
+/// that is not bound, alone or in a list, is a [`Value::Ident`].
+pub(crate) fn eval_symbol(expr: &Spanned<Expr>, scope: &Scope) -> Result<Value, EvalError> {
+    match &expr.0 {
+        Expr::Token(CodeToken::Ident(name)) if !is_keyword(name) && scope.value(name).is_none() => {
+            Ok(Value::Ident(name.to_string()))
+        }
+        Expr::Token(CodeToken::List(items)) => Ok(Value::List(
+            items
+                .iter()
+                .map(|item| eval_symbol(item, scope))
+                .collect::<Result<_, _>>()?,
+        )),
+        _ => eval_expr(expr, scope),
+    }
+}
+
+fn is_keyword(name: &str) -> bool {
+    matches!(name, "true" | "false" | "none")
+}
+
+fn eval_token(token: &CodeToken, scope: &Scope, span: Span) -> Result<Value, EvalError> {
+    let value = match token {
+        CodeToken::Ident("true") => Value::Bool(true),
+        CodeToken::Ident("false") => Value::Bool(false),
+        CodeToken::Ident("none") => Value::None,
+        CodeToken::Ident(name) => scope
+            .value(name)
+            .ok_or_else(|| EvalError::new(span, format!("unknown variable '{}'", name)))?,
+        CodeToken::Str(value) => Value::Str(value.clone()),
+        CodeToken::Int(value) => Value::Int(*value),
+        CodeToken::Float(value) => Value::Float(*value),
+        CodeToken::Length(value) => Value::Length(*value),
+        CodeToken::Color(value) => Value::Color(value.clone()),
+        CodeToken::List(items) => Value::List(
+            items
+                .iter()
+                .map(|item| eval_expr(item, scope))
+                .collect::<Result<_, _>>()?,
+        ),
+        CodeToken::Dict(entries) => Value::Dict(
+            entries
+                .iter()
+                .map(|(key, value)| Ok((key.to_string(), eval_expr(value, scope)?)))
+                .collect::<Result<BTreeMap<_, _>, EvalError>>()?,
+        ),
+    };
+
+    Ok(value)
+}
+
+fn eval_bool(expr: &Spanned<Expr>, op: &str, scope: &Scope) -> Result<bool, EvalError> {
+    let value = eval_expr(expr, scope)?;
+
+    value.as_bool().ok_or_else(|| {
+        EvalError::new(
+            expr.1,
+            format!("'{}' needs a bool, found {}", op, value.type_name()),
+        )
+    })
+}
+
+fn unary(op: &str, operand: Value) -> Result<Value, String> {
+    match (op, operand) {
+        (Op::NEGATIVE, Value::Int(value)) => value
+            .checked_neg()
+            .map(Value::Int)
+            .ok_or_else(|| String::from("integer overflow")),
+        (Op::NEGATIVE, Value::Float(value)) => Ok(Value::Float(-value)),
+        (Op::NEGATIVE, Value::Length(value)) => Ok(Value::Length(-value)),
+        (Op::NOT, Value::Bool(value)) => Ok(Value::Bool(!value)),
+        (op, operand) => Err(format!("can not apply '{}' to {}", op, operand.type_name())),
+    }
+}
+
+fn binary(lhs: Value, op: &str, rhs: Value) -> Result<Value, String> {
+    let mismatch = |lhs: &Value, rhs: &Value| {
+        format!(
+            "can not apply '{}' to {} and {}",
+            op,
+            lhs.type_name(),
+            rhs.type_name()
+        )
+    };
+
+    if op == Op::DIVIDE && (rhs.as_number() == Some(0.0) || rhs.as_length() == Some(Length::ZERO)) {
+        return Err(String::from("division by zero"));
+    }
+
+    let value = match (op, &lhs, &rhs, lhs.as_number(), rhs.as_number()) {
+        (Op::ADD | Op::SUBTRACT | Op::MULTIPLY, Value::Int(a), Value::Int(b), _, _) => {
+            let result = match op {
+                Op::ADD => a.checked_add(*b),
+                Op::SUBTRACT => a.checked_sub(*b),
+                _ => a.checked_mul(*b),
+            };
+
+            Value::Int(result.ok_or_else(|| String::from("integer overflow"))?)
+        }
+        (Op::ADD | Op::SUBTRACT | Op::MULTIPLY | Op::DIVIDE, _, _, Some(a), Some(b)) => {
+            Value::Float(match op {
+                Op::ADD => a + b,
+                Op::SUBTRACT => a - b,
+                Op::MULTIPLY => a * b,
+                _ => a / b,
+            })
+        }
+        (Op::ADD, Value::Str(a), Value::Str(b), _, _) => Value::Str(format!("{}{}", a, b)),
+        (Op::ADD, Value::Length(a), Value::Length(b), _, _) => Value::Length(*a + *b),
+        (Op::SUBTRACT, Value::Length(a), Value::Length(b), _, _) => Value::Length(*a - *b),
+        (Op::MULTIPLY, Value::Length(length), _, _, Some(factor))
+        | (Op::MULTIPLY, _, Value::Length(length), Some(factor), _) => {
+            Value::Length(*length * factor)
+        }
+        (Op::DIVIDE, Value::Length(length), _, _, Some(divisor)) => {
+            Value::Length(*length * (1.0 / divisor))
+        }
+        // Relative lengths are only known during layout
+        (Op::DIVIDE, Value::Length(a), Value::Length(b), _, _)
+            if a.is_absolute() && b.is_absolute() =>
+        {
+            Value::Float((a.abs / b.abs).0)
+        }
+        (Op::EQUALS, ..) => Value::Bool(equals(&lhs, &rhs)),
+        (Op::NOT_EQUALS, ..) => Value::Bool(!equals(&lhs, &rhs)),
+        (Op::GREATER | Op::GREATER_EQUALS | Op::LESS | Op::LESS_EQUALS, ..) => {
+            let ordering = match compare(&lhs, &rhs) {
+                Some(ordering) => ordering,
+                None if lhs.as_length().is_some() && rhs.as_length().is_some() => {
+                    return Err(String::from(
+                        "relative lengths can not be compared before the layout",
+                    ))
+                }
+                None => return Err(mismatch(&lhs, &rhs)),
+            };
+
+            Value::Bool(match op {
+                Op::GREATER => ordering == Ordering::Greater,
+                Op::GREATER_EQUALS => ordering != Ordering::Less,
+                Op::LESS => ordering == Ordering::Less,
+                _ => ordering != Ordering::Greater,
+            })
+        }
+        _ => return Err(mismatch(&lhs, &rhs)),
+    };
+
+    Ok(value)
+}
+
+/// Values of different types are never equal, except for ints and floats.
+fn equals(lhs: &Value, rhs: &Value) -> bool {
+    match (lhs.as_number(), rhs.as_number()) {
+        (Some(a), Some(b)) => a == b,
+        _ => lhs == rhs,
+    }
+}
+
+fn compare(lhs: &Value, rhs: &Value) -> Option<Ordering> {
+    match (lhs, rhs) {
+        (Value::Int(a), Value::Int(b)) => Some(a.cmp(b)),
+        (Value::Length(a), Value::Length(b)) if a.is_absolute() && b.is_absolute() => {
+            a.abs.partial_cmp(&b.abs)
+        }
+        (Value::Str(a), Value::Str(b)) => Some(a.cmp(b)),
+        _ => lhs.as_number()?.partial_cmp(&rhs.as_number()?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acryl_core::math::{Length, Mm, Percent, Pt};
+    use acryl_parser::{parse, source_map::SourceMap, ParsedFile};
+
+    use super::{eval_expr, eval_symbol};
+    use crate::{
+        scope::{Binding, Scope},
+        Value,
+    };
+
+    /// Evaluates `source` as the value of a header field, with `n` bound to 10.
+    fn eval_with(source: &str, symbolic: bool) -> Result<Value, String> {
+        let mut sources = SourceMap::new();
+        let file = sources.add("test.acryl", format!("doc {{ x: {} }}", source));
+
+        let Ok(ParsedFile::Doc(doc)) = parse(sources.get(file)) else {
+            panic!("'{}' does not parse", source);
+        };
+
+        let scope = Scope::default();
+        scope.define("n", Binding::Value(Value::Int(10)));
+
+        let expr = doc.header().get("x").expect("the field is parsed");
+        let value = if symbolic {
+            eval_symbol(expr, &scope)
+        } else {
+            eval_expr(expr, &scope)
+        };
+
+        value.map_err(|error| error.message)
+    }
+
+    fn eval(source: &str) -> Result<Value, String> {
+        eval_with(source, false)
+    }
+
+    #[test]
+    fn arithmetic_on_ints_and_floats() {
+        assert_eq!(eval("1 + 2 * 3"), Ok(Value::Int(7)));
+        assert_eq!(eval("2*-3"), Ok(Value::Int(-6)));
+        assert_eq!(eval("7 / 2"), Ok(Value::Float(3.5)));
+        assert_eq!(eval("1 + 0.5"), Ok(Value::Float(1.5)));
+        assert_eq!(eval("n - 1"), Ok(Value::Int(9)));
+    }
+
+    #[test]
+    fn arithmetic_errors() {
+        assert_eq!(eval("1 / 0"), Err(String::from("division by zero")));
+        assert_eq!(
+            eval("9223372036854775807 + 1"),
+            Err(String::from("integer overflow"))
+        );
+        assert_eq!(
+            eval("\"a\" - 1"),
+            Err(String::from("can not apply '-' to str and int"))
+        );
+        assert_eq!(
+            eval("-true"),
+            Err(String::from("can not apply '-' to bool"))
+        );
+    }
+
+    #[test]
+    fn strings_and_lengths() {
+        assert_eq!(eval("\"a\" + \"b\""), Ok(Value::Str(String::from("ab"))));
+        assert_eq!(
+            eval("50% - 2mm"),
+            Ok(Value::Length(
+                Length::from(Percent(50.0)) - Length::from(Mm(2.0))
+            ))
+        );
+        assert_eq!(eval("2 * 3pt"), Ok(Value::Length(Length::from(Pt(6.0)))));
+        assert_eq!(eval("6pt / 2pt"), Ok(Value::Float(3.0)));
+        assert_eq!(
+            eval("50% > 1pt"),
+            Err(String::from(
+                "relative lengths can not be compared before the layout"
+            ))
+        );
+    }
+
+    #[test]
+    fn comparisons() {
+        assert_eq!(eval("n > 1"), Ok(Value::Bool(true)));
+        assert_eq!(eval("1 == 1.0"), Ok(Value::Bool(true)));
+        assert_eq!(eval("1 == \"1\""), Ok(Value::Bool(false)));
+        assert_eq!(eval("x==-1"), Err(String::from("unknown variable 'x'")));
+        assert_eq!(eval("\"a\" < \"b\""), Ok(Value::Bool(true)));
+        assert_eq!(eval("1cm >= 10mm"), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn boolean_operators_short_circuit() {
+        assert_eq!(eval("true || missing"), Ok(Value::Bool(true)));
+        assert_eq!(eval("false && missing"), Ok(Value::Bool(false)));
+        assert_eq!(
+            eval("true && missing"),
+            Err(String::from("unknown variable 'missing'"))
+        );
+        assert_eq!(
+            eval("1 && true"),
+            Err(String::from("'&&' needs a bool, found int"))
+        );
+    }
+
+    #[test]
+    fn lists_dicts_and_fields() {
+        assert_eq!(
+            eval("[n, none]"),
+            Ok(Value::List(vec![Value::Int(10), Value::None]))
+        );
+        assert_eq!(eval("{ a: { b: n } }.a.b"), Ok(Value::Int(10)));
+        assert_eq!(
+            eval("{ a: 1 }.b"),
+            Err(String::from("dict has no field 'b'"))
+        );
+    }
+
+    #[test]
+    fn unbound_names_are_only_symbols_where_names_are_expected() {
+        assert_eq!(eval("red"), Err(String::from("unknown variable 'red'")));
+        assert_eq!(
+            eval_with("red", true),
+            Ok(Value::Ident(String::from("red")))
+        );
+        assert_eq!(
+            eval_with("[std, n]", true),
+            Ok(Value::List(vec![
+                Value::Ident(String::from("std")),
+                Value::Int(10)
+            ]))
+        );
+        assert_eq!(
+            eval_with("red + 1", true),
+            Err(String::from("unknown variable 'red'"))
+        );
+    }
+}
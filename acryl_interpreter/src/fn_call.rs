@@ -0,0 +1,219 @@
+use std::rc::Rc;
+
+use acryl_core::{
+    math::{Length, Pt},
+    Color,
+};
+use acryl_parser::{
+    ast::{Argument, ContentToken, Expr},
+    Span, Spanned,
+};
+
+use crate::{
+    expr::{eval_expr, eval_symbol},
+    scope::Scope,
+    EvalError, Value,
+};
+
+pub struct FnCall<'a, 'src> {
+    pub name: &'src str,
+    pub key: Option<&'src str>,
+    pub arguments: Arguments<'a, 'src>,
+    pub content: &'a [ContentToken<'src>],
+    pub span: Span,
+    /// The scope of the caller, which the arguments are evaluated in.
+    pub(crate) scope: Rc<Scope<'src>>,
+}
+
+#[derive(Clone, Copy)]
+pub struct Arguments<'a, 'src> {
+    arguments: &'a [Argument<'src>],
+}
+
+impl<'a, 'src> Arguments<'a, 'src> {
+    pub fn new(arguments: &'a [Argument<'src>]) -> Self {
+        Self { arguments }
+    }
+
+    /// Returns the argument called `name`, or the `index`-th unnamed argument if there is none.
+    pub fn get(&self, index: usize, name: &str) -> Option<&'a Spanned<Expr<'src>>> {
+        let named = self.arguments.iter().find_map(|argument| match argument {
+            Argument::Named { name: n, value } if *n == name => Some(value),
+            _ => None,
+        });
+
+        named.or_else(|| {
+            self.arguments
+                .iter()
+                .filter_map(|argument| match argument {
+                    Argument::Unnamed(value) => Some(value),
+                    _ => None,
+                })
+                .nth(index)
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.arguments.is_empty()
+    }
+
+    /// The names of the named arguments and the number of unnamed ones.
+    pub(crate) fn names(&self) -> (Vec<(&'src str, Span)>, usize) {
+        let mut names = Vec::new();
+        let mut unnamed = 0;
+
+        for argument in self.arguments {
+            match argument {
+                Argument::Named { name, value } => names.push((*name, value.1)),
+                Argument::Unnamed(_) => unnamed += 1,
+            }
+        }
+
+        (names, unnamed)
+    }
+}
+
+impl<'a, 'src> FnCall<'a, 'src> {
+    pub fn error<M: Into<String>>(&self, message: M) -> EvalError {
+        EvalError::new(self.span, message)
+    }
+
+    /// Evaluates the argument called `name`, or the `index`-th unnamed argument if there is none.
+    pub fn value(&self, index: usize, name: &str) -> Result<Option<Value>, EvalError> {
+        self.arguments
+            .get(index, name)
+            .map(|argument| eval_expr(argument, &self.scope))
+            .transpose()
+    }
+
+    /// An identifier that is not bound to a value, like a label or an alignment.
+    pub fn ident(&self, index: usize, name: &str) -> Result<String, EvalError> {
+        self.typed_with(index, name, "an identifier", eval_symbol, |value| {
+            value.as_ident().map(ToOwned::to_owned)
+        })
+    }
+
+    pub fn str(&self, index: usize, name: &str) -> Result<String, EvalError> {
+        self.typed(index, name, "a string", |value| match value {
+            Value::Str(value) => Some(value),
+            _ => None,
+        })
+    }
+
+    pub fn int(&self, index: usize, name: &str) -> Result<i64, EvalError> {
+        self.typed(index, name, "an int", |value| match value {
+            Value::Int(value) => Some(value),
+            _ => None,
+        })
+    }
+
+    pub fn number(&self, index: usize, name: &str) -> Result<f64, EvalError> {
+        self.typed(index, name, "a number", |value| value.as_number())
+    }
+
+    /// Plain numbers are lengths in points.
+    pub fn length(&self, index: usize, name: &str) -> Result<Length, EvalError> {
+        self.typed(index, name, "a length", |value| match value {
+            Value::Length(value) => Some(value),
+            value => value.as_number().map(|value| Pt(value).into()),
+        })
+    }
+
+    /// Colors can also be given by their CSS name.
+    pub fn color(&self, index: usize, name: &str) -> Result<Color, EvalError> {
+        self.typed_with(index, name, "a color", eval_symbol, |value| {
+            value.as_color()
+        })
+    }
+
+    pub fn list(&self, index: usize, name: &str) -> Result<Vec<Value>, EvalError> {
+        self.typed(index, name, "a list", |value| match value {
+            Value::List(value) => Some(value),
+            _ => None,
+        })
+    }
+
+    pub fn bool(&self, index: usize, name: &str) -> Result<bool, EvalError> {
+        self.typed(index, name, "a bool", |value| value.as_bool())
+    }
+
+    /// Like [`FnCall::ident`], but returns `default` if the argument is missing.
+    pub fn ident_or(&self, index: usize, name: &str, default: &str) -> Result<String, EvalError> {
+        match self.arguments.get(index, name) {
+            Some(_) => self.ident(index, name),
+            None => Ok(default.to_owned()),
+        }
+    }
+
+    /// Like [`FnCall::int`], but returns `default` if the argument is missing.
+    pub fn int_or(&self, index: usize, name: &str, default: i64) -> Result<i64, EvalError> {
+        match self.arguments.get(index, name) {
+            Some(_) => self.int(index, name),
+            None => Ok(default),
+        }
+    }
+
+    /// Like [`FnCall::number`], but returns `default` if the argument is missing.
+    pub fn number_or(&self, index: usize, name: &str, default: f64) -> Result<f64, EvalError> {
+        match self.arguments.get(index, name) {
+            Some(_) => self.number(index, name),
+            None => Ok(default),
+        }
+    }
+
+    /// Like [`FnCall::bool`], but returns `default` if the argument is missing.
+    pub fn bool_or(&self, index: usize, name: &str, default: bool) -> Result<bool, EvalError> {
+        match self.arguments.get(index, name) {
+            Some(_) => self.bool(index, name),
+            None => Ok(default),
+        }
+    }
+
+    /// Like [`FnCall::length`], but returns `default` if the argument is missing.
+    pub fn length_or(
+        &self,
+        index: usize,
+        name: &str,
+        default: Length,
+    ) -> Result<Length, EvalError> {
+        match self.arguments.get(index, name) {
+            Some(_) => self.length(index, name),
+            None => Ok(default),
+        }
+    }
+
+    /// Evaluates a required argument and converts it with `convert`, `kind` describes the expected type.
+    fn typed<T, F: FnOnce(Value) -> Option<T>>(
+        &self,
+        index: usize,
+        name: &str,
+        kind: &str,
+        convert: F,
+    ) -> Result<T, EvalError> {
+        self.typed_with(index, name, kind, eval_expr, convert)
+    }
+
+    /// Like [`FnCall::typed`], but the argument is evaluated with `eval`.
+    fn typed_with<T, F: FnOnce(Value) -> Option<T>>(
+        &self,
+        index: usize,
+        name: &str,
+        kind: &str,
+        eval: fn(&Spanned<Expr<'src>>, &Scope<'src>) -> Result<Value, EvalError>,
+        convert: F,
+    ) -> Result<T, EvalError> {
+        let argument = self.arguments.get(index, name).ok_or_else(|| {
+            self.error(format!("missing argument '{}' for '\\{}'", name, self.name))
+        })?;
+
+        convert(eval(argument, &self.scope)?).ok_or_else(|| {
+            EvalError::new(
+                argument.1,
+                format!(
+                    "argument '{}' of '\\{}' needs to be {}",
+                    name, self.name, kind
+                ),
+            )
+        })
+    }
+}
@@ -0,0 +1,105 @@
+use acryl_core::{math::Length, Color};
+use acryl_parser::{Span, Spanned};
+
+use crate::{data::DataFormat, scope::Scope, EvalError, Value};
+
+/// Calls one of the functions that can be used in expressions.
+///
+/// Besides the color constructors, `json`, `csv` and `toml` load data files.
+pub(crate) fn call_function(
+    name: &str,
+    arguments: Vec<Spanned<Value>>,
+    span: Span,
+    scope: &Scope,
+) -> Result<Value, EvalError> {
+    let color = match name {
+        "rgb" => {
+            let [r, g, b] = color_components(name, arguments, span)?;
+            Color::RGB(r, g, b)
+        }
+        "cmyk" => {
+            let [c, m, y, k] = color_components(name, arguments, span)?;
+            Color::CMYK(c, m, y, k)
+        }
+        "gray" => {
+            let [value] = color_components(name, arguments, span)?;
+            Color::Gray(value)
+        }
+        "json" | "csv" | "toml" => return load_data(name, arguments, span, scope),
+        _ => return Err(EvalError::new(span, format!("unknown function '{}'", name))),
+    };
+
+    Ok(Value::Color(color))
+}
+
+/// Loads a data file, named like its format.
+///
+/// Without the data files of the document, like in the header, paths start in the working directory.
+fn load_data(
+    name: &str,
+    arguments: Vec<Spanned<Value>>,
+    span: Span,
+    scope: &Scope,
+) -> Result<Value, EvalError> {
+    let [(Value::Str(path), path_span)] = arguments.as_slice() else {
+        return Err(EvalError::new(
+            span,
+            format!("'{}' expects the path of a file as a string", name),
+        ));
+    };
+
+    let format: DataFormat = name.parse().expect("the name of a data format");
+
+    let value = match scope.data_files() {
+        Some(data_files) => data_files.load(format, path, span),
+        None => format.load(path),
+    };
+
+    value.map_err(|message| EvalError::new(*path_span, message))
+}
+
+fn color_components<const N: usize>(
+    name: &str,
+    arguments: Vec<Spanned<Value>>,
+    span: Span,
+) -> Result<[u8; N], EvalError> {
+    if arguments.len() != N {
+        return Err(EvalError::new(
+            span,
+            format!(
+                "'{}' expects {} arguments, got {}",
+                name,
+                N,
+                arguments.len()
+            ),
+        ));
+    }
+
+    let mut components = [0; N];
+
+    for (component, (value, span)) in components.iter_mut().zip(arguments) {
+        *component = color_component(&value).ok_or_else(|| {
+            EvalError::new(
+                span,
+                "color components need to be ints from 0 to 255, floats from 0 to 1 or percentages",
+            )
+        })?;
+    }
+
+    Ok(components)
+}
+
+fn color_component(value: &Value) -> Option<u8> {
+    let fraction = match value {
+        Value::Int(value) => return u8::try_from(*value).ok(),
+        Value::Float(value) => *value,
+        Value::Length(length) if *length == Length::from(length.percent) => {
+            length.percent.0 / 100.0
+        }
+        _ => return None,
+    };
+
+    (0.0..=1.0)
+        .contains(&fraction)
+        .then(|| (fraction * 255.0).round() as u8)
+}
@@ -0,0 +1,53 @@
+use std::collections::BTreeMap;
+
+use acryl_parser::{file::DocFileHeader, Span};
+
+use crate::{expr::eval_symbol, scope::Scope, EvalError, Value};
+
+/// The evaluated fields of the header of a document.
+pub struct Header {
+    fields: BTreeMap<String, (Value, Span)>,
+}
+
+impl Header {
+    pub fn eval(header: &DocFileHeader) -> Result<Self, Vec<EvalError>> {
+        let mut fields = BTreeMap::new();
+        let mut errors = Vec::new();
+        let scope = Scope::default();
+
+        // Fields like `pageSize: A4` or `import: [std]` name things with identifiers
+        for (name, expr) in header.fields() {
+            match eval_symbol(expr, &scope) {
+                Ok(value) => {
+                    fields.insert(name.to_owned(), (value, expr.1));
+                }
+                Err(error) => errors.push(error),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(Self { fields })
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.fields.get(name).map(|(value, _)| value)
+    }
+
+    /// The fields as a dict, which is how documents read them.
+    pub fn to_value(&self) -> Value {
+        Value::Dict(
+            self.fields
+                .iter()
+                .map(|(name, (value, _))| (name.clone(), value.clone()))
+                .collect(),
+        )
+    }
+
+    /// Where the value of the field is written.
+    pub fn span(&self, name: &str) -> Option<Span> {
+        self.fields.get(name).map(|(_, span)| *span)
+    }
+}
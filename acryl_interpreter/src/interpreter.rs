@@ -0,0 +1,1301 @@
+use std::{borrow::Cow, collections::BTreeMap, mem, rc::Rc};
+
+use acryl_core::math::Pt;
+use acryl_layout::{
+    hyphenation::Hyphenation,
+    node::Node,
+    page_template::{Heading, PageInfo, PageRegion},
+    paragraph::{ParagraphStyle, TextRun, TextStyle},
+};
+use acryl_parser::{
+    ast::{ContentToken, Expr, Param},
+    Span, Spanned,
+};
+use acryl_pdf::font::FontRef;
+
+use crate::{
+    builtins::{get_builtin, get_inline_builtin, get_style_builtin},
+    expr::eval_expr,
+    fn_call::Arguments,
+    scope::{Binding, Function, Scope},
+    DataFiles, EvalError, FnCall, Header, Label, Value,
+};
+
+pub struct Interpreter<'a> {
+    pub(crate) style: ParagraphStyle,
+    /// The page a header or footer is evaluated for.
+    pub(crate) page: Option<PageInfo>,
+    pub(crate) header: Option<Region<'a>>,
+    pub(crate) footer: Option<Region<'a>>,
+    pub(crate) scope: Rc<Scope<'a>>,
+    /// The names that are visible in the document and in all modules, like the header.
+    globals: Rc<Scope<'a>>,
+    /// The content of the included files, by the start of the span of their `\include` call.
+    pub(crate) includes: BTreeMap<usize, &'a [ContentToken<'a>]>,
+    /// The fonts that can be chosen with `\set`, by their name.
+    pub(crate) fonts: BTreeMap<String, FontRef>,
+    /// The labels of the previous pass, which references are resolved with.
+    pub(crate) labels: BTreeMap<String, Label>,
+    /// The labels defined in this pass, with the section they are in.
+    defined: BTreeMap<String, Label>,
+    /// References to labels that are not known yet.
+    pub(crate) unresolved: Vec<EvalError>,
+    /// The numbers of the current section and the sections it is in, counted by `\heading`.
+    pub(crate) sections: Vec<usize>,
+    /// The number of a heading, which is put before the first word of its content.
+    pub(crate) prefix: Option<String>,
+    /// The key of the call that is evaluated, which labels the first heading in it.
+    pub(crate) key: Option<&'a str>,
+    /// The headings of the previous pass with their pages, which are listed in the table of
+    /// contents.
+    pub(crate) headings: Vec<Heading>,
+    /// How many calls of defined functions are evaluated at the moment.
+    depth: usize,
+    errors: Vec<EvalError>,
+}
+
+/// The content of a header or footer, which is evaluated for every page.
+pub(crate) struct Region<'a> {
+    pub content: &'a [ContentToken<'a>],
+    pub style: ParagraphStyle,
+    pub height: Option<Pt>,
+    pub spacing: Pt,
+    pub scope: Rc<Scope<'a>>,
+    pub includes: BTreeMap<usize, &'a [ContentToken<'a>]>,
+    pub fonts: BTreeMap<String, FontRef>,
+    pub labels: BTreeMap<String, Label>,
+}
+
+impl Region<'_> {
+    pub const DEFAULT_SPACING: Pt = Pt(12.0);
+}
+
+/// The definitions of a logic file, which documents and other logic files import.
+#[derive(Clone)]
+pub struct Module<'a> {
+    scope: Rc<Scope<'a>>,
+}
+
+/// The result of evaluating a document.
+pub struct Evaluated<'a> {
+    pub nodes: Vec<Node>,
+    pub header: Option<PageRegion<'a>>,
+    pub footer: Option<PageRegion<'a>>,
+    /// The labels defined in the document, their page and position are only known after the
+    /// layout.
+    pub labels: BTreeMap<String, Label>,
+    /// References to labels that were not known, most of them are resolved in the next pass.
+    pub unresolved: Vec<EvalError>,
+}
+
+impl<'a> Interpreter<'a> {
+    pub const DEFAULT_FONT_SIZE: f64 = 12.0;
+    /// Limits the recursion of defined functions.
+    const MAX_DEPTH: usize = 64;
+    /// Inserts the content of another file, which is loaded before the evaluation.
+    pub const INCLUDE: &'static str = "include";
+    /// The parameter of a defined function that receives the content of a call.
+    const CONTENT_PARAM: &'static str = "content";
+    /// The name the fields of the header are bound to.
+    const HEADER: &'static str = "doc";
+    /// Sets the style of its content, or of the rest of the content it is in if it has none.
+    const SET: &'static str = "set";
+
+    pub fn new(font: FontRef) -> Self {
+        Self::with_style(ParagraphStyle::new(font, Self::DEFAULT_FONT_SIZE))
+    }
+
+    fn with_style(style: ParagraphStyle) -> Self {
+        let globals = Rc::new(Scope::default());
+
+        Self {
+            style,
+            page: None,
+            header: None,
+            footer: None,
+            scope: Scope::child(&globals),
+            globals,
+            includes: BTreeMap::new(),
+            fonts: BTreeMap::new(),
+            labels: BTreeMap::new(),
+            defined: BTreeMap::new(),
+            unresolved: Vec::new(),
+            sections: Vec::new(),
+            prefix: None,
+            key: None,
+            headings: Vec::new(),
+            depth: 0,
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn with_hyphenation(mut self, hyphenation: Hyphenation) -> Self {
+        self.style.hyphenation = Some(hyphenation);
+        self
+    }
+
+    /// Data files are loaded through `data_files`, so they are only read once for all passes.
+    pub fn with_data_files(self, data_files: Rc<DataFiles>) -> Self {
+        self.globals.set_data_files(data_files);
+        self
+    }
+
+    /// Makes the fields of the header readable as `doc`, like `\(doc.title)`.
+    pub fn with_header(self, header: &Header) -> Self {
+        self.globals
+            .define(Self::HEADER, Binding::Value(header.to_value()));
+        self
+    }
+
+    /// Binds `value` to `name` in the document and in all modules, like data given on the command
+    /// line.
+    pub fn with_value(self, name: &'a str, value: Value) -> Self {
+        self.globals.define(name, Binding::Value(value));
+        self
+    }
+
+    /// Sets the fonts that can be chosen with `\set(font: name)`.
+    pub fn with_fonts(mut self, fonts: BTreeMap<String, FontRef>) -> Self {
+        self.fonts = fonts;
+        self
+    }
+
+    /// Sets the labels of the previous pass, which `\ref` and `\pageref` are resolved with.
+    pub fn with_labels(mut self, labels: BTreeMap<String, Label>) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Sets the headings of the previous pass, which `\tableOfContents` lists.
+    pub fn with_headings(mut self, headings: Vec<Heading>) -> Self {
+        self.headings = headings;
+        self
+    }
+
+    /// Evaluates the content of a document into layout nodes.
+    ///
+    /// Evaluation continues after an error, so all errors of the document are returned at once.
+    pub fn eval(mut self, tokens: &'a [ContentToken<'a>]) -> Result<Evaluated<'a>, Vec<EvalError>> {
+        let nodes = self.eval_content(tokens);
+
+        let header = self.header.take();
+        let footer = self.footer.take();
+
+        // Regions are only evaluated after the layout, so their errors are found with a stand-in page
+        for region in header.iter().chain(&footer) {
+            let mut interpreter = Region::eval(region, &PageInfo::new(1, 1, BTreeMap::new())).1;
+            self.errors.append(&mut interpreter.errors);
+            self.unresolved.append(&mut interpreter.unresolved);
+        }
+
+        if self.errors.is_empty() {
+            Ok(Evaluated {
+                nodes,
+                header: header.map(Region::into_page_region),
+                footer: footer.map(Region::into_page_region),
+                labels: self.defined,
+                unresolved: self.unresolved,
+            })
+        } else {
+            Err(self.errors)
+        }
+    }
+
+    /// Sets the content of the files included with `\include`, by the start of the span of the call.
+    pub fn with_includes(mut self, includes: BTreeMap<usize, &'a [ContentToken<'a>]>) -> Self {
+        self.includes = includes;
+        self
+    }
+
+    /// Evaluates the definitions of a logic file, they can use the definitions of `imports`.
+    pub fn eval_module(
+        &mut self,
+        definitions: &'a [ContentToken<'a>],
+        imports: &[Module<'a>],
+    ) -> Module<'a> {
+        // The imports are kept out of the scope of the module, so they are not imported with it
+        let imported = Scope::child(&self.globals);
+
+        for import in imports {
+            imported.import(&import.scope);
+        }
+
+        let outer = mem::replace(&mut self.scope, Scope::child(&imported));
+        self.eval_content(definitions);
+
+        Module {
+            scope: mem::replace(&mut self.scope, outer),
+        }
+    }
+
+    /// Makes the definitions of `module` visible in the document.
+    pub fn import(&mut self, module: &Module<'a>) {
+        self.scope.import(&module.scope);
+    }
+
+    pub fn eval_content(&mut self, tokens: &'a [ContentToken<'a>]) -> Vec<Node> {
+        let mut builder = ContentBuilder::default();
+
+        self.eval_tokens(tokens, &mut builder);
+        builder.flush(&self.style);
+
+        builder.nodes
+    }
+
+    /// Evaluates `tokens` into `builder`, so that words continue the paragraph they are in.
+    fn eval_tokens(&mut self, tokens: &'a [ContentToken<'a>], builder: &mut ContentBuilder<'a>) {
+        for (index, token) in tokens.iter().enumerate() {
+            match token {
+                ContentToken::Word(word) => {
+                    self.push_prefix(builder);
+                    builder.push_word(Cow::Borrowed(*word), &self.style.text);
+                }
+                // Raw text is a single word, so its spaces are kept and it is never broken
+                ContentToken::Raw(text) => {
+                    self.push_prefix(builder);
+                    builder.push_word(Cow::Borrowed(*text), &self.style.text);
+                }
+                ContentToken::Space | ContentToken::LineBreak => builder.attached = false,
+                ContentToken::ParBreak => builder.flush(&self.style),
+                ContentToken::RawBlock(text) => {
+                    builder.flush(&self.style);
+
+                    builder.nodes.extend(
+                        raw_lines(text)
+                            .into_iter()
+                            .map(|line| Node::paragraph(vec![line.to_owned()], self.style.clone())),
+                    );
+                }
+                ContentToken::Def {
+                    name,
+                    params,
+                    body,
+                    span,
+                } => {
+                    if let Err(error) = self.define(name, params, body, *span) {
+                        self.errors.push(error);
+                    }
+                }
+                ContentToken::Let { name, value, .. } => match eval_expr(value, &self.scope) {
+                    Ok(value) => self.scope.define(name, Binding::Value(value)),
+                    Err(error) => self.errors.push(error),
+                },
+                ContentToken::Expr(expr) => match eval_expr(expr, &self.scope) {
+                    Ok(value) => self.push_text(builder, &value.to_string()),
+                    Err(error) => self.errors.push(error),
+                },
+                ContentToken::If {
+                    condition,
+                    then,
+                    otherwise,
+                } => {
+                    if let Err(error) = self.eval_if(condition, then, otherwise, builder) {
+                        self.errors.push(error);
+                    }
+                }
+                ContentToken::For {
+                    key,
+                    value,
+                    list,
+                    body,
+                } => {
+                    if let Err(error) = self.eval_for(*key, value, list, body, builder) {
+                        self.errors.push(error);
+                    }
+                }
+                ContentToken::Fn {
+                    name,
+                    key,
+                    arguments,
+                    content,
+                    span,
+                } => {
+                    // Without content, `\set` styles the rest of the content it is in
+                    let rest =
+                        *name == Self::SET && content.is_empty() && self.scope.get(name).is_none();
+
+                    let call = FnCall {
+                        name,
+                        key: *key,
+                        arguments: Arguments::new(arguments),
+                        content: if rest { &tokens[index + 1..] } else { content },
+                        span: *span,
+                        scope: self.scope.clone(),
+                    };
+
+                    // The label is placed before the content of the call, so it is found on the
+                    // page the content starts on
+                    if let Some(key) = key {
+                        builder.label(key);
+                    }
+
+                    let outer = key.map(|key| self.key.replace(key));
+
+                    if let Err(error) = self.eval_fn(&call, builder) {
+                        self.errors.push(error);
+                    }
+
+                    if let Some(outer) = outer {
+                        self.key = outer;
+                    }
+
+                    if let Some(key) = key {
+                        if let Err(error) = self.define_label(key, *span) {
+                            self.errors.push(error);
+                        }
+                    }
+
+                    // The rest of the tokens was the content of the call
+                    if rest {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Evaluates a call, names defined in the document hide the builtins.
+    fn eval_fn(
+        &mut self,
+        call: &FnCall<'a, 'a>,
+        builder: &mut ContentBuilder<'a>,
+    ) -> Result<(), EvalError> {
+        match self.scope.get(call.name) {
+            Some(Binding::Function(function)) => {
+                return self.call_defined(&function, call, builder)
+            }
+            Some(Binding::Value(value)) => {
+                no_arguments(call)?;
+                self.push_text(builder, &value.to_string());
+                return Ok(());
+            }
+            Some(Binding::Content(content, scope)) => {
+                no_arguments(call)?;
+                self.eval_in_scope(trim_space(content), Scope::child(&scope), builder);
+                return Ok(());
+            }
+            None => {}
+        }
+
+        // Included content is part of the surrounding content, like its definitions
+        if call.name == Self::INCLUDE {
+            let content = self
+                .includes
+                .get(&call.span.start)
+                .copied()
+                .ok_or_else(|| {
+                    call.error(format!(
+                        "'\\{}' needs the path of a file as a string",
+                        call.name
+                    ))
+                })?;
+
+            self.eval_tokens(content, builder);
+            return Ok(());
+        }
+
+        if let Some(builtin) = get_inline_builtin(call.name) {
+            let text = builtin(self, call)?;
+            self.push_text(builder, &text);
+            return Ok(());
+        }
+
+        // The content of a style continues the paragraph like the body of a defined function, only
+        // its words are in another style
+        if let Some(builtin) = get_style_builtin(call.name) {
+            let style = builtin(self, call)?;
+            let outer = mem::replace(&mut self.style.text, style);
+            self.eval_in_scope(trim_space(call.content), Scope::child(&self.scope), builder);
+            self.style.text = outer;
+            return Ok(());
+        }
+
+        // Other builtins are blocks, which end the paragraph in front of them
+        builder.flush(&self.style);
+
+        // Definitions in the content of a builtin are only visible there
+        let scope = Scope::child(&self.scope);
+        let outer = mem::replace(&mut self.scope, scope);
+        let result = self.call(call);
+        self.scope = outer;
+
+        builder.nodes.append(&mut result?);
+
+        Ok(())
+    }
+
+    /// Binds a function defined with `\def` in the current scope.
+    fn define(
+        &mut self,
+        name: &'a str,
+        params: &'a [Param<'a>],
+        body: &'a [ContentToken<'a>],
+        span: Span,
+    ) -> Result<(), EvalError> {
+        for (index, param) in params.iter().enumerate() {
+            if params[..index].iter().any(|other| other.name == param.name) {
+                return Err(EvalError::new(
+                    span,
+                    format!("duplicate parameter '{}' of '\\{}'", param.name, name),
+                ));
+            }
+
+            if let (Self::CONTENT_PARAM, Some((_, span))) = (param.name, &param.default) {
+                return Err(EvalError::new(
+                    *span,
+                    "the 'content' parameter can not have a default value",
+                ));
+            }
+        }
+
+        let function = Function {
+            params,
+            body,
+            scope: self.scope.clone(),
+        };
+
+        self.scope
+            .define(name, Binding::Function(Rc::new(function)));
+
+        Ok(())
+    }
+
+    /// Calls a function defined with `\def`, its body continues the surrounding paragraph.
+    ///
+    /// Arguments are evaluated in the scope of the caller, defaults in the scope of the function,
+    /// so they can refer to the parameters before them.
+    fn call_defined(
+        &mut self,
+        function: &Function<'a>,
+        call: &FnCall<'a, 'a>,
+        builder: &mut ContentBuilder<'a>,
+    ) -> Result<(), EvalError> {
+        if self.depth == Self::MAX_DEPTH {
+            return Err(call.error(format!(
+                "'\\{}' is nested more than {} times",
+                call.name,
+                Self::MAX_DEPTH
+            )));
+        }
+
+        let params = function
+            .params
+            .iter()
+            .filter(|param| param.name != Self::CONTENT_PARAM)
+            .collect::<Vec<_>>();
+        let (names, unnamed) = call.arguments.names();
+
+        if let Some((name, span)) = names
+            .iter()
+            .find(|(name, _)| !params.iter().any(|param| param.name == *name))
+        {
+            return Err(EvalError::new(
+                *span,
+                format!("'\\{}' has no parameter '{}'", call.name, name),
+            ));
+        }
+
+        if unnamed > params.len() {
+            return Err(call.error(format!(
+                "'\\{}' takes {} arguments, got {}",
+                call.name,
+                params.len(),
+                unnamed
+            )));
+        }
+
+        let scope = Scope::child(&function.scope);
+
+        for (index, param) in params.into_iter().enumerate() {
+            let value = match (call.arguments.get(index, param.name), &param.default) {
+                (Some(argument), _) => eval_expr(argument, &call.scope)?,
+                (None, Some(default)) => eval_expr(default, &scope)?,
+                (None, None) => {
+                    return Err(call.error(format!(
+                        "missing argument '{}' for '\\{}'",
+                        param.name, call.name
+                    )))
+                }
+            };
+
+            scope.define(param.name, Binding::Value(value));
+        }
+
+        if function
+            .params
+            .iter()
+            .any(|param| param.name == Self::CONTENT_PARAM)
+        {
+            scope.define(
+                Self::CONTENT_PARAM,
+                Binding::Content(call.content, call.scope.clone()),
+            );
+        } else if !trim_space(call.content).is_empty() {
+            return Err(call.error(format!("'\\{}' does not take content", call.name)));
+        }
+
+        self.depth += 1;
+        self.eval_in_scope(trim_space(function.body), scope, builder);
+        self.depth -= 1;
+
+        Ok(())
+    }
+
+    /// Evaluates one of the branches, which continue the surrounding paragraph like the body of a
+    /// defined function.
+    fn eval_if(
+        &mut self,
+        condition: &'a Spanned<Expr<'a>>,
+        then: &'a [ContentToken<'a>],
+        otherwise: &'a [ContentToken<'a>],
+        builder: &mut ContentBuilder<'a>,
+    ) -> Result<(), EvalError> {
+        let value = eval_expr(condition, &self.scope)?;
+        let condition = value.as_bool().ok_or_else(|| {
+            EvalError::new(
+                condition.1,
+                format!("'\\if' needs a bool, found {}", value.type_name()),
+            )
+        })?;
+
+        let branch = if condition { then } else { otherwise };
+        self.eval_in_scope(branch, Scope::child(&self.scope), builder);
+
+        Ok(())
+    }
+
+    /// Evaluates `body` for every item of a list or every entry of a dict, `key` is bound to the
+    /// index of the item or the key of the entry.
+    ///
+    /// Every pass gets its own scope, so the names defined in the body start over.
+    fn eval_for(
+        &mut self,
+        key: Option<&'a str>,
+        value: &'a str,
+        list: &'a Spanned<Expr<'a>>,
+        body: &'a [ContentToken<'a>],
+        builder: &mut ContentBuilder<'a>,
+    ) -> Result<(), EvalError> {
+        let entries = match eval_expr(list, &self.scope)? {
+            Value::List(items) => items
+                .into_iter()
+                .enumerate()
+                .map(|(index, item)| (Value::Int(index as i64), item))
+                .collect::<Vec<_>>(),
+            Value::Dict(entries) => entries
+                .into_iter()
+                .map(|(key, value)| (Value::Str(key), value))
+                .collect(),
+            other => {
+                return Err(EvalError::new(
+                    list.1,
+                    format!("'\\for' needs a list or dict, found {}", other.type_name()),
+                ))
+            }
+        };
+
+        for (entry_key, entry_value) in entries {
+            let scope = Scope::child(&self.scope);
+
+            if let Some(key) = key {
+                scope.define(key, Binding::Value(entry_key));
+            }
+
+            scope.define(value, Binding::Value(entry_value));
+
+            // The errors of the body are most likely the same in every pass, so they are only
+            // reported once
+            let errors = self.errors.len();
+            self.eval_in_scope(body, scope, builder);
+
+            if self.errors.len() > errors {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Puts the number of a heading before the first word of its content.
+    fn push_prefix(&mut self, builder: &mut ContentBuilder<'a>) {
+        if let Some(prefix) = self.prefix.take() {
+            builder.push_word(Cow::Owned(prefix), &self.style.text);
+            builder.attached = false;
+        }
+    }
+
+    fn push_text(&mut self, builder: &mut ContentBuilder<'a>, text: &str) {
+        self.push_prefix(builder);
+        builder.push_text(text, &self.style.text);
+    }
+
+    /// Defines the label of a call in the current section, its page is added after the layout.
+    fn define_label(&mut self, name: &str, span: Span) -> Result<(), EvalError> {
+        if self.defined.contains_key(name) {
+            return Err(EvalError::new(
+                span,
+                format!("label '{}' is already defined", name),
+            ));
+        }
+
+        let label = Label {
+            section: self.section(),
+            ..Label::default()
+        };
+
+        self.defined.insert(name.to_owned(), label);
+
+        Ok(())
+    }
+
+    /// The number of the current section, like `2.1`.
+    pub(crate) fn section(&self) -> Option<String> {
+        let numbers = self
+            .sections
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+
+        (!numbers.is_empty()).then(|| numbers.join("."))
+    }
+
+    /// The label of the previous pass that `call` refers to, labels that are not known are
+    /// recorded as unresolved.
+    pub(crate) fn label(&mut self, call: &FnCall) -> Result<Option<Label>, EvalError> {
+        let name = call.ident(0, "label")?;
+        let label = self.labels.get(&name).cloned();
+
+        if label.is_none() {
+            self.unresolved
+                .push(call.error(format!("unknown label '{}'", name)));
+        }
+
+        Ok(label)
+    }
+
+    fn eval_in_scope(
+        &mut self,
+        tokens: &'a [ContentToken<'a>],
+        scope: Rc<Scope<'a>>,
+        builder: &mut ContentBuilder<'a>,
+    ) {
+        let outer = mem::replace(&mut self.scope, scope);
+        self.eval_tokens(tokens, builder);
+        self.scope = outer;
+    }
+
+    /// Evaluates the content of `call`, which has to result in exactly one node.
+    pub fn eval_single(&mut self, call: &FnCall<'a, 'a>) -> Result<Node, EvalError> {
+        let mut nodes = self.eval_content(call.content);
+
+        match nodes.len() {
+            1 => Ok(nodes.remove(0)),
+            0 => Err(call.error(format!("'\\{}' needs content", call.name))),
+            _ => Err(call.error(format!(
+                "'\\{}' expects a single child, got {}",
+                call.name,
+                nodes.len()
+            ))),
+        }
+    }
+
+    /// The font called `name`, one of the fonts set with [`Interpreter::with_fonts`].
+    pub(crate) fn font(&self, name: &str, span: Span) -> Result<FontRef, EvalError> {
+        self.fonts.get(name).cloned().ok_or_else(|| {
+            let names = self
+                .fonts
+                .keys()
+                .map(|name| format!("'{}'", name))
+                .collect::<Vec<_>>();
+
+            EvalError::new(
+                span,
+                format!(
+                    "unknown font '{}', the fonts are {}",
+                    name,
+                    names.join(", ")
+                ),
+            )
+        })
+    }
+
+    /// The page that is evaluated, which is only known in headers and footers.
+    pub(crate) fn page(&self, call: &FnCall) -> Result<&PageInfo, EvalError> {
+        self.page.as_ref().ok_or_else(|| {
+            call.error(format!(
+                "'\\{}' can only be used in a header or footer",
+                call.name
+            ))
+        })
+    }
+
+    fn call(&mut self, call: &FnCall<'a, 'a>) -> Result<Vec<Node>, EvalError> {
+        match get_builtin(call.name) {
+            Some(builtin) => builtin(self, call),
+            None if call.name == "else" => {
+                Err(call.error("'\\else' needs to follow the content of an '\\if'"))
+            }
+            None => Err(call.error(format!("unknown function '\\{}'", call.name))),
+        }
+    }
+}
+
+/// Collects the nodes of content, words are kept until their paragraph ends.
+#[derive(Default)]
+struct ContentBuilder<'a> {
+    nodes: Vec<Node>,
+    /// The words of the current paragraph, by their style.
+    runs: Vec<Run<'a>>,
+    /// Whether the next word continues the last one, because there is no space in between
+    attached: bool,
+    /// Labels with the number of words before them, they are placed with the paragraph.
+    labels: Vec<(usize, &'a str)>,
+}
+
+/// Words of the same style, which become a [`TextRun`] when their paragraph ends.
+struct Run<'a> {
+    style: TextStyle,
+    words: Vec<Cow<'a, str>>,
+    attached: bool,
+}
+
+impl<'a> ContentBuilder<'a> {
+    fn push_word(&mut self, word: Cow<'a, str>, style: &TextStyle) {
+        match self.runs.last_mut() {
+            Some(run) if run.style == *style => match run.words.last_mut() {
+                Some(last) if self.attached => last.to_mut().push_str(&word),
+                _ => run.words.push(word),
+            },
+            _ => self.runs.push(Run {
+                style: style.clone(),
+                words: vec![word],
+                attached: self.attached,
+            }),
+        }
+
+        self.attached = true;
+    }
+
+    /// Adds text that may contain spaces, only its first word can continue the last one.
+    ///
+    /// Whitespace at the edges of the text separates it from the words around it, empty text
+    /// leaves them as they are.
+    fn push_text(&mut self, text: &str, style: &TextStyle) {
+        if text.starts_with(char::is_whitespace) {
+            self.attached = false;
+        }
+
+        for (index, word) in text.split_whitespace().enumerate() {
+            self.attached &= index == 0;
+            self.push_word(Cow::Owned(word.to_owned()), style);
+        }
+
+        if text.ends_with(char::is_whitespace) {
+            self.attached = false;
+        }
+    }
+
+    fn word_count(&self) -> usize {
+        self.runs.iter().map(|run| run.words.len()).sum()
+    }
+
+    /// Labels the content that comes next.
+    fn label(&mut self, name: &'a str) {
+        self.labels.push((self.word_count(), name));
+    }
+
+    /// Ends the current paragraph.
+    ///
+    /// Labels that are followed by words of the paragraph are placed before it, the others after it.
+    fn flush(&mut self, style: &ParagraphStyle) {
+        self.attached = false;
+
+        let count = self.word_count();
+        let (inside, after): (Vec<_>, Vec<_>) = mem::take(&mut self.labels)
+            .into_iter()
+            .partition(|(index, _)| *index < count);
+
+        self.nodes
+            .extend(inside.into_iter().map(|(_, name)| Node::label(name)));
+
+        if count > 0 {
+            let runs = mem::take(&mut self.runs)
+                .into_iter()
+                .map(|run| TextRun {
+                    style: run.style,
+                    words: run.words.into_iter().map(Cow::into_owned).collect(),
+                    attached: run.attached,
+                })
+                .collect();
+
+            self.nodes.push(Node::runs(runs, style.clone()));
+        }
+
+        self.nodes
+            .extend(after.into_iter().map(|(_, name)| Node::label(name)));
+    }
+}
+
+/// Values and content parameters are used like functions without arguments.
+fn no_arguments(call: &FnCall) -> Result<(), EvalError> {
+    if call.key.is_some() || !call.arguments.is_empty() {
+        return Err(call.error(format!("'\\{}' is not a function", call.name)));
+    }
+
+    if !call.content.is_empty() {
+        return Err(call.error(format!("'\\{}' does not take content", call.name)));
+    }
+
+    Ok(())
+}
+
+/// Leaves out the whitespace around content, like the spaces just inside its braces.
+fn trim_space<'a>(tokens: &'a [ContentToken<'a>]) -> &'a [ContentToken<'a>] {
+    let is_space = |token: &ContentToken| {
+        matches!(
+            token,
+            ContentToken::Space | ContentToken::LineBreak | ContentToken::ParBreak
+        )
+    };
+
+    let start = tokens
+        .iter()
+        .position(|token| !is_space(token))
+        .unwrap_or(tokens.len());
+    let end = tokens
+        .iter()
+        .rposition(|token| !is_space(token))
+        .map_or(start, |index| index + 1);
+
+    &tokens[start..end]
+}
+
+/// The lines of a raw block without the indentation they have in common, blank lines at the start
+/// and end are left out.
+fn raw_lines(text: &str) -> Vec<&str> {
+    let lines = text.lines().map(str::trim_end).collect::<Vec<_>>();
+
+    let start = lines
+        .iter()
+        .position(|line| !line.is_empty())
+        .unwrap_or(lines.len());
+    let end = lines
+        .iter()
+        .rposition(|line| !line.is_empty())
+        .map_or(start, |index| index + 1);
+
+    // Tabs and spaces are not the same indentation, so only the whitespace all lines start with is
+    // removed
+    let indent = lines[start..end]
+        .iter()
+        .filter(|line| !line.is_empty())
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .reduce(|indent, other| {
+            let common = indent
+                .chars()
+                .zip(other.chars())
+                .take_while(|(a, b)| a == b)
+                .map(|(c, _)| c.len_utf8())
+                .sum();
+
+            &indent[..common]
+        })
+        .unwrap_or_default();
+
+    lines[start..end]
+        .iter()
+        .map(|line| line.strip_prefix(indent).unwrap_or(line))
+        .collect()
+}
+
+impl<'a> Region<'a> {
+    /// Evaluates the region for `page`, the interpreter is returned with the errors it found.
+    fn eval(&self, page: &PageInfo) -> (Vec<Node>, Interpreter<'a>) {
+        let mut interpreter = Interpreter::with_style(self.style.clone());
+        interpreter.page = Some(page.clone());
+        interpreter.scope = self.scope.clone();
+        interpreter.includes = self.includes.clone();
+        interpreter.fonts = self.fonts.clone();
+        interpreter.labels = self.labels.clone();
+
+        let nodes = interpreter.eval_content(self.content);
+
+        (nodes, interpreter)
+    }
+
+    fn into_page_region(self) -> PageRegion<'a> {
+        let (height, spacing) = (self.height, self.spacing);
+
+        // Errors were already reported when the document was evaluated
+        PageRegion::new(move |page: &PageInfo| self.eval(page).0, height, spacing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{borrow::Cow, collections::BTreeMap};
+
+    use acryl_core::Color;
+    use acryl_layout::{node::Node, paragraph::TextStyle, FONT_DEJAVU_SERIF};
+    use acryl_parser::{parse, parse_content, source_map::SourceMap, ParsedFile};
+    use acryl_pdf::{
+        font::{Font, FontRef},
+        resource_manager::ResourceManager,
+    };
+
+    use crate::{Header, Label};
+
+    use super::{ContentBuilder, Evaluated, Interpreter};
+
+    fn font() -> FontRef {
+        let font = Font::load(FONT_DEJAVU_SERIF).expect("the default font is installed");
+        ResourceManager::new().add_font(font)
+    }
+
+    /// Evaluates `source` as content, returns the text of every node with text or the messages of
+    /// the errors.
+    fn eval(source: &str) -> Result<Vec<String>, Vec<String>> {
+        let mut sources = SourceMap::new();
+        let file = sources.add("test.acryl", source.to_owned());
+        let tokens = parse_content(sources.get(file)).expect("the content parses");
+
+        let result = match Interpreter::new(font()).eval(&tokens) {
+            Ok(evaluated) => Ok(evaluated
+                .nodes
+                .iter()
+                .map(Node::plain_text)
+                .filter(|text| !text.is_empty())
+                .collect()),
+            Err(errors) => Err(errors.into_iter().map(|error| error.message).collect()),
+        };
+
+        result
+    }
+
+    /// Evaluates `source` with the labels of a previous pass, returns the text of every node, the
+    /// labels it defines and the messages of the unresolved references.
+    fn eval_labels(
+        source: &str,
+        labels: BTreeMap<String, Label>,
+    ) -> (Vec<String>, BTreeMap<String, Label>, Vec<String>) {
+        let mut sources = SourceMap::new();
+        let file = sources.add("test.acryl", source.to_owned());
+        let tokens = parse_content(sources.get(file)).expect("the content parses");
+
+        let Ok(Evaluated {
+            nodes,
+            labels,
+            unresolved,
+            ..
+        }) = Interpreter::new(font()).with_labels(labels).eval(&tokens)
+        else {
+            panic!("errors while evaluating {:?}", source);
+        };
+
+        let text = nodes
+            .iter()
+            .map(Node::plain_text)
+            .filter(|text| !text.is_empty())
+            .collect();
+        let unresolved = unresolved.into_iter().map(|error| error.message).collect();
+
+        (text, labels, unresolved)
+    }
+
+    /// Evaluates `source` as content, returns the source text the errors point at.
+    fn error_spans(source: &str) -> Vec<String> {
+        let mut sources = SourceMap::new();
+        let file = sources.add("test.acryl", source.to_owned());
+        let tokens = parse_content(sources.get(file)).expect("the content parses");
+
+        let spans = match Interpreter::new(font()).eval(&tokens) {
+            Ok(_) => Vec::new(),
+            Err(errors) => errors
+                .into_iter()
+                .map(|error| source[error.span.into_range()].to_owned())
+                .collect(),
+        };
+
+        spans
+    }
+
+    /// Evaluates `source` as a single paragraph, returns its words with their font size.
+    fn sizes(source: &str) -> Vec<(String, f64)> {
+        let mut sources = SourceMap::new();
+        let file = sources.add("test.acryl", source.to_owned());
+        let tokens = parse_content(sources.get(file)).expect("the content parses");
+
+        let mut interpreter = Interpreter::new(font());
+        let mut builder = ContentBuilder::default();
+        interpreter.eval_tokens(&tokens, &mut builder);
+        assert!(interpreter.errors.is_empty(), "errors while evaluating {:?}", source);
+
+        builder
+            .runs
+            .iter()
+            .flat_map(|run| {
+                run.words
+                    .iter()
+                    .map(|word| (word.to_string(), run.style.font_size))
+            })
+            .collect()
+    }
+
+    fn words(parts: &[&str]) -> Vec<String> {
+        let style = TextStyle::new(font(), 12.0);
+        let mut builder = ContentBuilder::default();
+
+        for part in parts {
+            match *part {
+                " " => builder.attached = false,
+                text => builder.push_text(text, &style),
+            }
+        }
+
+        builder
+            .runs
+            .iter()
+            .flat_map(|run| run.words.iter().map(ToString::to_string))
+            .collect()
+    }
+
+    #[test]
+    fn text_continues_the_word_in_front_of_it() {
+        assert_eq!(words(&["a", "b c", "d"]), vec!["ab", "cd"]);
+    }
+
+    #[test]
+    fn empty_text_keeps_the_space_around_it() {
+        assert_eq!(words(&["a", " ", "", " ", "b"]), vec!["a", "b"]);
+        assert_eq!(words(&["a", "", "b"]), vec!["ab"]);
+    }
+
+    #[test]
+    fn whitespace_at_the_edges_separates_text() {
+        assert_eq!(words(&["a", " b ", "c"]), vec!["a", "b", "c"]);
+        assert_eq!(words(&["a", "  ", "b"]), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn a_new_style_starts_a_run() {
+        let plain = TextStyle::new(font(), 12.0);
+        let red = TextStyle {
+            color: Some(Color::RGB(255, 0, 0)),
+            ..plain.clone()
+        };
+
+        let mut builder = ContentBuilder::default();
+        builder.push_word(Cow::Borrowed("a"), &plain);
+        builder.attached = false;
+        builder.push_word(Cow::Borrowed("b"), &red);
+        builder.push_word(Cow::Borrowed("c"), &plain);
+
+        let runs = builder
+            .runs
+            .iter()
+            .map(|run| (run.words.join(" "), run.attached, run.style == red))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            runs,
+            vec![
+                ("a".to_owned(), false, false),
+                ("b".to_owned(), false, true),
+                ("c".to_owned(), true, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn styles_continue_the_paragraph() {
+        assert_eq!(
+            eval("a \\set(color: red){ b } \\fontSize(20pt){c}d e"),
+            Ok(vec!["a b cd e".to_owned()])
+        );
+        assert_eq!(
+            eval("a \\set(fontSize: 8pt) b\n\nc"),
+            Ok(vec!["a b".to_owned(), "c".to_owned()])
+        );
+    }
+
+    #[test]
+    fn blocks_end_the_paragraph() {
+        assert_eq!(
+            eval("a \\align(center){b} c"),
+            Ok(vec!["a".to_owned(), "b".to_owned(), "c".to_owned()])
+        );
+    }
+
+    #[test]
+    fn let_is_visible_until_the_end_of_its_content() {
+        assert_eq!(
+            eval("\\let x = \"a\"\n\\(x) \\set(color: red) { \\let x = \"b\"\n\\(x) } \\(x)"),
+            Ok(vec!["a b a".to_owned()])
+        );
+        assert_eq!(
+            eval("\\let x = 1\n\\let x = x + 1\n\\(x)"),
+            Ok(vec!["2".to_owned()])
+        );
+        assert_eq!(
+            eval("\\set(color: red) { \\let y = 1\n} \\(y)"),
+            Err(vec!["unknown variable 'y'".to_owned()])
+        );
+    }
+
+    #[test]
+    fn definitions_use_the_scope_they_are_defined_in() {
+        assert_eq!(
+            eval(
+                "\\let x = \"outer\"\n\\def F() { \\(x) }\n\
+                 \\set(color: red) { \\let x = \"inner\"\n\\F() }"
+            ),
+            Ok(vec!["outer".to_owned()])
+        );
+    }
+
+    #[test]
+    fn content_reads_the_header() {
+        let mut sources = SourceMap::new();
+        let file = sources.add(
+            "test.acryl",
+            String::from("doc { author: \"Armin\" }\nby \\(doc.author)"),
+        );
+        let Ok(ParsedFile::Doc(doc)) = parse(sources.get(file)) else {
+            panic!("the document parses");
+        };
+        let header = Header::eval(doc.header()).expect("the header is valid");
+
+        let text = match Interpreter::new(font())
+            .with_header(&header)
+            .eval(doc.content().tokens())
+        {
+            Ok(evaluated) => evaluated.nodes.iter().map(Node::plain_text).collect(),
+            Err(_) => Vec::new(),
+        };
+
+        assert_eq!(text, vec!["by Armin".to_owned()]);
+    }
+
+    #[test]
+    fn set_styles_its_content_or_the_rest_of_the_content_it_is_in() {
+        let words = |words: &[(&str, f64)]| {
+            words
+                .iter()
+                .map(|(word, size)| (word.to_string(), *size))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            sizes("a \\set(fontSize: 20pt) { b } c"),
+            words(&[("a", 12.0), ("b", 20.0), ("c", 12.0)])
+        );
+        assert_eq!(
+            sizes("a \\set(color: red) { b \\set(fontSize: 8pt) c } d \\set(fontSize: 2em) e"),
+            words(&[("a", 12.0), ("b", 12.0), ("c", 8.0), ("d", 12.0), ("e", 24.0)])
+        );
+    }
+
+    #[test]
+    fn if_evaluates_one_branch() {
+        assert_eq!(
+            eval("\\if(1 < 2) { yes } \\else { no }"),
+            Ok(vec!["yes".to_owned()])
+        );
+        assert_eq!(eval("\\if(false) { yes }"), Ok(vec![]));
+        assert_eq!(
+            eval("\\let n = 3\n\\if(n == 1) { one } \\else \\if(n == 2) { two } \\else { many }"),
+            Ok(vec!["many".to_owned()])
+        );
+    }
+
+    #[test]
+    fn for_evaluates_the_body_for_every_item() {
+        assert_eq!(
+            eval("\\for(x in [1, 2, 3]) { (\\(x)) }"),
+            Ok(vec!["(1) (2) (3)".to_owned()])
+        );
+        assert_eq!(
+            eval("\\for(i, x in [\"a\", \"b\"]) { \\(i)=\\(x) }"),
+            Ok(vec!["0=a 1=b".to_owned()])
+        );
+        assert_eq!(
+            eval("\\for(key, value in { a: 1, b: 2 }) { \\(key):\\(value) }"),
+            Ok(vec!["a:1 b:2".to_owned()])
+        );
+    }
+
+    #[test]
+    fn loop_variables_are_scoped_to_the_body() {
+        assert_eq!(
+            eval("\\let x = 0\n\\for(x in [1, 2]) { \\let y = x\n\\(y) } \\(x)"),
+            Ok(vec!["1 2 0".to_owned()])
+        );
+        assert_eq!(
+            eval("\\for(x in [1]) { } \\(x)"),
+            Err(vec!["unknown variable 'x'".to_owned()])
+        );
+    }
+
+    #[test]
+    fn control_flow_errors_point_at_the_expression() {
+        assert_eq!(
+            eval("\\if(1) { a }"),
+            Err(vec!["'\\if' needs a bool, found int".to_owned()])
+        );
+        assert_eq!(error_spans("a \\if(1 + 1) { a }"), ["1 + 1"]);
+        assert_eq!(
+            eval("\\for(x in 3) { a }"),
+            Err(vec!["'\\for' needs a list or dict, found int".to_owned()])
+        );
+        assert_eq!(error_spans("\\for(x in \"abc\") { a }"), ["\"abc\""]);
+        // An error in the body is only reported once
+        assert_eq!(error_spans("\\for(x in [1, 2]) { \\(y) }"), ["y"]);
+    }
+
+    #[test]
+    fn labels_are_defined_in_their_section() {
+        let (_, labels, _) = eval_labels(
+            "\\heading[a](1) { A }\n\\heading(2) { B }\n\\set[b](color: red) { text }",
+            BTreeMap::new(),
+        );
+
+        let sections = labels
+            .iter()
+            .map(|(name, label)| (name.as_str(), label.section.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(sections, vec![("a", Some("1")), ("b", Some("1.1"))]);
+
+        assert_eq!(
+            eval("\\set[a](color: red) { a } \\set[a](color: red) { b }"),
+            Err(vec!["label 'a' is already defined".to_owned()])
+        );
+    }
+
+    #[test]
+    fn references_use_the_labels_of_the_previous_pass() {
+        let label = Label {
+            section: Some("2.1".to_owned()),
+            page: Some(3),
+            ..Label::default()
+        };
+        let (text, _, unresolved) = eval_labels(
+            "see \\ref(intro) on page \\pageref(intro)",
+            BTreeMap::from([("intro".to_owned(), label)]),
+        );
+
+        assert_eq!(text, vec!["see 2.1 on page 3".to_owned()]);
+        assert!(unresolved.is_empty());
+    }
+
+    #[test]
+    fn unknown_labels_are_unresolved() {
+        let (text, _, unresolved) =
+            eval_labels("see \\ref(intro), \\pageref(intro)", BTreeMap::new());
+
+        assert_eq!(text, vec!["see ??, ??".to_owned()]);
+        assert_eq!(
+            unresolved,
+            vec!["unknown label 'intro'".to_owned(), "unknown label 'intro'".to_owned()]
+        );
+    }
+}
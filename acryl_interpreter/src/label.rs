@@ -0,0 +1,15 @@
+use acryl_core::math::{Pt, Vector2};
+
+/// An element that was labelled with the key of a call, like `\Section[intro] { .. }`.
+///
+/// The section is known after the evaluation, the page and position only after the layout, so
+/// references use the labels of the previous pass.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Label {
+    /// The number of the section the element is in, like `2.1`.
+    pub section: Option<String>,
+    /// The number of the page the element starts on, starting at 1.
+    pub page: Option<usize>,
+    /// The top left corner of the element on its page.
+    pub position: Option<Vector2<Pt>>,
+}
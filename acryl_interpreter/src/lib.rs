@@ -0,0 +1,19 @@
+mod builtins;
+mod data;
+mod error;
+mod expr;
+mod fn_call;
+mod functions;
+mod header;
+mod interpreter;
+mod label;
+mod scope;
+mod value;
+
+pub use data::{DataFiles, DataFormat};
+pub use error::EvalError;
+pub use fn_call::{Arguments, FnCall};
+pub use header::Header;
+pub use interpreter::{Evaluated, Interpreter, Module};
+pub use label::Label;
+pub use value::Value;
@@ -0,0 +1,89 @@
+use std::{
+    cell::{OnceCell, RefCell},
+    collections::BTreeMap,
+    rc::Rc,
+};
+
+use acryl_parser::ast::{ContentToken, Param};
+
+use crate::{DataFiles, Value};
+
+/// The names that are visible at a point of the document.
+///
+/// Every function call and content block gets its own scope, so definitions are only visible in
+/// the block they are made in, and in the functions defined there.
+#[derive(Default)]
+pub(crate) struct Scope<'a> {
+    parent: Option<Rc<Scope<'a>>>,
+    bindings: RefCell<BTreeMap<&'a str, Binding<'a>>>,
+    /// Only set for the outermost scope, the data files are the same for all of the document.
+    data_files: OnceCell<Rc<DataFiles>>,
+}
+
+#[derive(Clone)]
+pub(crate) enum Binding<'a> {
+    Value(Value),
+    /// The content of a call, which is evaluated with the scope of the caller.
+    Content(&'a [ContentToken<'a>], Rc<Scope<'a>>),
+    Function(Rc<Function<'a>>),
+}
+
+/// A function defined with `\def`, it keeps the scope of its definition.
+pub(crate) struct Function<'a> {
+    pub params: &'a [Param<'a>],
+    pub body: &'a [ContentToken<'a>],
+    pub scope: Rc<Scope<'a>>,
+}
+
+impl<'a> Scope<'a> {
+    pub fn child(parent: &Rc<Self>) -> Rc<Self> {
+        Rc::new(Self {
+            parent: Some(parent.clone()),
+            bindings: RefCell::default(),
+            data_files: OnceCell::new(),
+        })
+    }
+
+    /// Binds `name` in this scope, hiding bindings of the same name in outer scopes.
+    pub fn define(&self, name: &'a str, binding: Binding<'a>) {
+        self.bindings.borrow_mut().insert(name, binding);
+    }
+
+    /// Binds the names `other` defines itself, the ones of its outer scopes are left out.
+    pub fn import(&self, other: &Self) {
+        let bindings = other.bindings.borrow();
+
+        self.bindings.borrow_mut().extend(
+            bindings
+                .iter()
+                .map(|(name, binding)| (*name, binding.clone())),
+        );
+    }
+
+    pub fn get(&self, name: &str) -> Option<Binding<'a>> {
+        match self.bindings.borrow().get(name) {
+            Some(binding) => Some(binding.clone()),
+            None => self.parent.as_ref()?.get(name),
+        }
+    }
+
+    /// Sets the data files of this scope and the scopes in it, they can only be set once.
+    pub fn set_data_files(&self, data_files: Rc<DataFiles>) {
+        let _ = self.data_files.set(data_files);
+    }
+
+    pub fn data_files(&self) -> Option<&DataFiles> {
+        match self.data_files.get() {
+            Some(data_files) => Some(data_files),
+            None => self.parent.as_ref()?.data_files(),
+        }
+    }
+
+    /// Returns the value bound to `name`, functions and content have no value.
+    pub fn value(&self, name: &str) -> Option<Value> {
+        match self.get(name)? {
+            Binding::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use std::{collections::BTreeMap, fmt};
+
+use acryl_core::{math::Length, Color};
+
+/// The result of evaluating an expression.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// The absence of a value, like `null` in JSON data.
+    None,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Length(Length),
+    Color(Color),
+    List(Vec<Value>),
+    Dict(BTreeMap<String, Value>),
+    /// A name that is used as is, like the name of an alignment.
+    Ident(String),
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Bool(_) => "bool",
+            Self::Int(_) => "int",
+            Self::Float(_) => "float",
+            Self::Str(_) => "str",
+            Self::Length(_) => "length",
+            Self::Color(_) => "color",
+            Self::List(_) => "list",
+            Self::Dict(_) => "dict",
+            Self::Ident(_) => "ident",
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_ident(&self) -> Option<&str> {
+        match self {
+            Self::Ident(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Str(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Self::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns ints and floats as a float.
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Self::Int(value) => Some(*value as f64),
+            Self::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_length(&self) -> Option<Length> {
+        match self {
+            Self::Length(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Value]> {
+        match self {
+            Self::List(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_dict(&self) -> Option<&BTreeMap<String, Value>> {
+        match self {
+            Self::Dict(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns colors, and identifiers that name a CSS color.
+    pub fn as_color(&self) -> Option<Color> {
+        match self {
+            Self::Color(value) => Some(value.clone()),
+            Self::Ident(name) => Color::named(name),
+            _ => None,
+        }
+    }
+}
+
+/// The text a value is inserted as into content.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => Ok(()),
+            Self::Bool(value) => write!(f, "{}", value),
+            Self::Int(value) => write!(f, "{}", value),
+            Self::Float(value) => write!(f, "{}", value),
+            Self::Str(value) | Self::Ident(value) => f.write_str(value),
+            Self::Length(length) => {
+                let parts = [
+                    (length.abs.0, "pt"),
+                    (length.em.0, "em"),
+                    (length.percent.0, "%"),
+                ]
+                .into_iter()
+                .filter(|(value, _)| *value != 0.0)
+                .map(|(value, unit)| format!("{}{}", value, unit))
+                .collect::<Vec<_>>();
+
+                if parts.is_empty() {
+                    f.write_str("0pt")
+                } else {
+                    f.write_str(&parts.join(" + "))
+                }
+            }
+            Self::Color(Color::Gray(gray)) => write!(f, "gray({})", gray),
+            Self::Color(Color::RGB(r, g, b)) => write!(f, "#{:02x}{:02x}{:02x}", r, g, b),
+            Self::Color(Color::CMYK(c, m, y, k)) => write!(f, "cmyk({}, {}, {}, {})", c, m, y, k),
+            Self::List(items) => {
+                let items = items.iter().map(Value::to_string).collect::<Vec<_>>();
+                f.write_str(&items.join(", "))
+            }
+            Self::Dict(entries) => {
+                let entries = entries
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key, value))
+                    .collect::<Vec<_>>();
+                f.write_str(&entries.join(", "))
+            }
+        }
+    }
+}
@@ -31,6 +31,18 @@ impl<T: VectorComponent> DySize<T> {
             DySize::MixMax { max: size, .. } => size,
         }
     }
+
+    /// The size the node would like to have, which is its min size if it has no preference.
+    pub fn preferred(&self) -> &Vector2<T> {
+        match &self {
+            DySize::Fixed(size) => size,
+            DySize::MixMax {
+                preferred: Some(size),
+                ..
+            } => size,
+            DySize::MixMax { min: size, .. } => size,
+        }
+    }
 }
 
 impl<T: VectorComponent> Add<Vector2<T>> for DySize<T> {
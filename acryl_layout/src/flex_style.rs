@@ -0,0 +1,19 @@
+use acryl_core::{math::Length, CrossAxisAlignment, MainAxisAlignment};
+
+#[derive(Debug, Default, Clone)]
+pub struct FlexStyle {
+    /// Minimal space between two children, percentages are relative to the space along the flex.
+    pub spacing: Length,
+    pub main_axis_alignment: MainAxisAlignment,
+    pub cross_axis_alignment: CrossAxisAlignment,
+}
+
+/// How a flexible child of a flex node uses the space it is given.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum FlexFit {
+    /// The child fills all of its space.
+    #[default]
+    Tight,
+    /// The child may be smaller than its space.
+    Loose,
+}
@@ -0,0 +1,41 @@
+use std::str::FromStr;
+
+/// Languages with embedded hyphenation patterns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    English,
+    German,
+}
+
+impl Language {
+    /// The TeX pattern file of the language.
+    pub fn patterns(self) -> &'static str {
+        match self {
+            Language::English => include_str!("../../assets/hyphenation/hyph-en-us.tex"),
+            Language::German => include_str!("../../assets/hyphenation/hyph-de-1996.tex"),
+        }
+    }
+
+    /// Minimal number of chars before and after a hyphen.
+    pub fn hyphen_min(self) -> (usize, usize) {
+        match self {
+            Language::English => (2, 3),
+            Language::German => (2, 2),
+        }
+    }
+}
+
+/// Parses a language tag like `en`, `en-US` or `de`, only the primary subtag is used.
+impl FromStr for Language {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let primary = s.split(['-', '_']).next().unwrap_or_default();
+
+        match primary.to_ascii_lowercase().as_str() {
+            "en" => Ok(Language::English),
+            "de" => Ok(Language::German),
+            _ => Err(()),
+        }
+    }
+}
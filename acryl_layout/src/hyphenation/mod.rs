@@ -0,0 +1,196 @@
+mod language;
+mod patterns;
+
+use std::rc::Rc;
+
+pub use language::Language;
+pub use patterns::{PatternError, PatternSet};
+
+/// Penalty of breaking a line at an inserted hyphen, the same as TeX's default `\hyphenpenalty`.
+pub const DEFAULT_HYPHEN_PENALTY: f64 = 50.0;
+
+/// Finds the positions at which words may be hyphenated, using Liang's algorithm.
+#[derive(Debug)]
+pub struct Hyphenator {
+    set: PatternSet,
+    left_min: usize,
+    right_min: usize,
+}
+
+impl Hyphenator {
+    pub fn new(set: PatternSet, left_min: usize, right_min: usize) -> Self {
+        Self {
+            set,
+            left_min,
+            right_min,
+        }
+    }
+
+    /// Parses a TeX pattern file, see [`PatternSet::parse`].
+    pub fn from_tex(source: &str, left_min: usize, right_min: usize) -> Result<Self, PatternError> {
+        Ok(Self::new(PatternSet::parse(source)?, left_min, right_min))
+    }
+
+    /// Loads the embedded patterns of a language.
+    pub fn language(language: Language) -> Self {
+        let (left_min, right_min) = language.hyphen_min();
+
+        Self::from_tex(language.patterns(), left_min, right_min)
+            .expect("embedded patterns are valid")
+    }
+
+    /// Returns the byte offsets in `word` at which a hyphen may be inserted.
+    ///
+    /// Leading and trailing chars that are not letters, like punctuation, are ignored.
+    pub fn hyphenate(&self, word: &str) -> Vec<usize> {
+        let trimmed = word.trim_start_matches(|c: char| !c.is_alphabetic());
+        let start = word.len() - trimmed.len();
+        let core = trimmed.trim_end_matches(|c: char| !c.is_alphabetic());
+
+        // Byte offset of every char in `word`
+        let offsets = core
+            .char_indices()
+            .map(|(index, _)| start + index)
+            .collect::<Vec<_>>();
+
+        // Multi-char lowercase mappings are truncated, so the indices keep matching the word
+        let letters = core
+            .chars()
+            .map(|ch| ch.to_lowercase().next().unwrap_or(ch))
+            .collect::<Vec<_>>();
+
+        if letters.len() < self.left_min + self.right_min {
+            return Vec::new();
+        }
+
+        let breaks = match self.set.exceptions.get(&letters.iter().collect::<String>()) {
+            Some(breaks) => breaks.clone(),
+            None => self.pattern_breaks(&letters),
+        };
+
+        breaks
+            .into_iter()
+            .filter(|index| *index >= self.left_min && *index <= letters.len() - self.right_min)
+            .map(|index| offsets[index])
+            .collect()
+    }
+
+    /// Char indices before which the patterns allow a break.
+    fn pattern_breaks(&self, letters: &[char]) -> Vec<usize> {
+        let dotted = std::iter::once('.')
+            .chain(letters.iter().copied())
+            .chain(std::iter::once('.'))
+            .collect::<Vec<_>>();
+
+        let mut priorities = vec![0u8; dotted.len() + 1];
+        let mut key = String::new();
+
+        for start in 0..dotted.len() {
+            key.clear();
+
+            for (len, ch) in dotted[start..].iter().take(self.set.max_len).enumerate() {
+                key.push(*ch);
+
+                if let Some(pattern) = self.set.patterns.get(&key) {
+                    let range = &mut priorities[start..=start + len + 1];
+
+                    for (priority, pattern_priority) in range.iter_mut().zip(pattern) {
+                        *priority = (*priority).max(*pattern_priority);
+                    }
+                }
+            }
+        }
+
+        // The priority before letter `i` is at `i + 1` because of the leading dot
+        (1..letters.len())
+            .filter(|index| priorities[index + 1] % 2 == 1)
+            .collect()
+    }
+}
+
+/// Hyphenation settings of a paragraph.
+#[derive(Debug, Clone)]
+pub struct Hyphenation {
+    pub hyphenator: Rc<Hyphenator>,
+    /// Penalty of breaking a line at an inserted or soft hyphen.
+    pub penalty: f64,
+}
+
+impl Hyphenation {
+    pub fn new(hyphenator: Rc<Hyphenator>) -> Self {
+        Self {
+            hyphenator,
+            penalty: DEFAULT_HYPHEN_PENALTY,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Hyphenator, Language};
+
+    /// The word with a hyphen at every position the hyphenator allows.
+    fn hyphenated(hyphenator: &Hyphenator, word: &str) -> String {
+        let mut parts = Vec::new();
+        let mut start = 0;
+
+        for offset in hyphenator.hyphenate(word) {
+            parts.push(&word[start..offset]);
+            start = offset;
+        }
+
+        parts.push(&word[start..]);
+        parts.join("-")
+    }
+
+    #[test]
+    fn patterns_allow_breaks_at_odd_priorities() {
+        let hyphenator = Hyphenator::from_tex("\\patterns{ 1ba a2b }", 1, 1).unwrap();
+
+        assert_eq!(hyphenated(&hyphenator, "abba"), "ab-ba");
+        assert_eq!(hyphenated(&hyphenator, "aba"), "aba");
+    }
+
+    #[test]
+    fn dots_match_the_edges_of_a_word() {
+        let hyphenator = Hyphenator::from_tex("\\patterns{ .a1 1c. }", 1, 1).unwrap();
+
+        assert_eq!(hyphenated(&hyphenator, "abac"), "a-ba-c");
+        assert_eq!(hyphenated(&hyphenator, "bab"), "bab");
+    }
+
+    #[test]
+    fn exceptions_replace_the_patterns() {
+        let source = "\\patterns{ 1b } \\hyphenation{ ab-bb }";
+        let hyphenator = Hyphenator::from_tex(source, 1, 1).unwrap();
+
+        assert_eq!(hyphenated(&hyphenator, "abbb"), "ab-bb");
+        assert_eq!(hyphenated(&hyphenator, "abb"), "a-b-b");
+    }
+
+    #[test]
+    fn breaks_keep_the_minimal_number_of_chars() {
+        let hyphenator = Hyphenator::from_tex("\\patterns{ 1b }", 2, 3).unwrap();
+
+        assert_eq!(hyphenated(&hyphenator, "abbbbb"), "ab-b-bbb");
+        assert_eq!(hyphenated(&hyphenator, "abbb"), "abbb");
+    }
+
+    #[test]
+    fn punctuation_and_case_are_ignored() {
+        let hyphenator = Hyphenator::from_tex("\\patterns{ 1b }", 1, 1).unwrap();
+
+        assert_eq!(hyphenated(&hyphenator, "(ABB),"), "(A-B-B),");
+        assert_eq!(hyphenated(&hyphenator, "äbb"), "ä-b-b");
+    }
+
+    #[test]
+    fn embedded_languages() {
+        let english = Hyphenator::language(Language::English);
+        let german = Hyphenator::language(Language::German);
+
+        assert_eq!(hyphenated(&english, "hyphenation"), "hy-phen-ation");
+        assert_eq!(hyphenated(&english, "computer"), "com-puter");
+        assert_eq!(hyphenated(&german, "Silbentrennung"), "Sil-ben-tren-nung");
+    }
+}
@@ -0,0 +1,172 @@
+use std::{collections::HashMap, fmt::Display};
+
+/// Patterns and exceptions read from a TeX hyphenation file.
+#[derive(Debug, Default)]
+pub struct PatternSet {
+    /// Letters of a pattern mapped to the priorities between them, one more than there are letters.
+    pub patterns: HashMap<String, Vec<u8>>,
+    /// Words mapped to the char indices at which they may be broken.
+    pub exceptions: HashMap<String, Vec<usize>>,
+    /// Length of the longest pattern in chars.
+    pub max_len: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternError {
+    UnknownCommand(String),
+    UnclosedGroup(&'static str),
+    InvalidPattern(String),
+}
+
+impl Display for PatternError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatternError::UnknownCommand(command) => write!(f, "unknown command '\\{}'", command),
+            PatternError::UnclosedGroup(command) => write!(f, "'\\{}' is never closed", command),
+            PatternError::InvalidPattern(pattern) => write!(f, "invalid pattern '{}'", pattern),
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+impl PatternSet {
+    /// Parses the `\patterns{...}` and `\hyphenation{...}` groups of a TeX file.
+    ///
+    /// Everything after a `%` up to the end of the line is a comment.
+    pub fn parse(source: &str) -> Result<Self, PatternError> {
+        let source = source
+            .lines()
+            .map(|line| line.split('%').next().unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut set = Self::default();
+        let mut rest = source.as_str();
+
+        while let Some(start) = rest.find('\\') {
+            rest = &rest[start + 1..];
+
+            let name_len = rest
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(rest.len());
+            let (name, after) = rest.split_at(name_len);
+
+            let command = match name {
+                "patterns" => "patterns",
+                "hyphenation" => "hyphenation",
+                name => return Err(PatternError::UnknownCommand(name.to_owned())),
+            };
+
+            let body = after
+                .trim_start()
+                .strip_prefix('{')
+                .ok_or(PatternError::UnclosedGroup(command))?;
+            let end = body.find('}').ok_or(PatternError::UnclosedGroup(command))?;
+
+            for word in body[..end].split_whitespace() {
+                match command {
+                    "patterns" => set.add_pattern(word)?,
+                    _ => set.add_exception(word),
+                }
+            }
+
+            rest = &body[end + 1..];
+        }
+
+        Ok(set)
+    }
+
+    /// Adds a pattern like `1na`, where the digits are the priorities between the letters.
+    fn add_pattern(&mut self, pattern: &str) -> Result<(), PatternError> {
+        let mut letters = String::new();
+        let mut priorities = vec![0];
+
+        for ch in pattern.chars() {
+            match ch.to_digit(10) {
+                Some(digit) => {
+                    let last = priorities.last_mut().expect("starts with one priority");
+
+                    if *last != 0 {
+                        return Err(PatternError::InvalidPattern(pattern.to_owned()));
+                    }
+
+                    *last = digit as u8;
+                }
+                None => {
+                    letters.push(ch);
+                    priorities.push(0);
+                }
+            }
+        }
+
+        if letters.is_empty() {
+            return Err(PatternError::InvalidPattern(pattern.to_owned()));
+        }
+
+        self.max_len = self.max_len.max(priorities.len() - 1);
+        self.patterns.insert(letters, priorities);
+
+        Ok(())
+    }
+
+    /// Adds an exception like `as-so-ciate`, which may only be broken at the hyphens.
+    fn add_exception(&mut self, exception: &str) {
+        let mut word = String::new();
+        let mut breaks = Vec::new();
+        let mut len = 0;
+
+        for ch in exception.chars() {
+            if ch == '-' {
+                breaks.push(len);
+            } else {
+                word.push(ch);
+                len += 1;
+            }
+        }
+
+        self.exceptions.insert(word, breaks);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PatternError, PatternSet};
+
+    #[test]
+    fn patterns_keep_the_priorities_between_their_letters() {
+        let set = PatternSet::parse("\\patterns{\n.ab1c 2d % a comment 1x\n}").unwrap();
+
+        assert_eq!(set.patterns.get(".abc"), Some(&vec![0, 0, 0, 1, 0]));
+        assert_eq!(set.patterns.get("d"), Some(&vec![2, 0]));
+        assert_eq!(set.patterns.get("x"), None);
+        assert_eq!(set.max_len, 4);
+    }
+
+    #[test]
+    fn exceptions_are_broken_at_their_hyphens() {
+        let set = PatternSet::parse("\\hyphenation{ as-so-ciate }").unwrap();
+
+        assert_eq!(set.exceptions.get("associate"), Some(&vec![2, 4]));
+    }
+
+    #[test]
+    fn invalid_files_are_errors() {
+        assert_eq!(
+            PatternSet::parse("\\foo{}").unwrap_err(),
+            PatternError::UnknownCommand("foo".to_owned())
+        );
+        assert_eq!(
+            PatternSet::parse("\\patterns{ a1").unwrap_err(),
+            PatternError::UnclosedGroup("patterns")
+        );
+        assert_eq!(
+            PatternSet::parse("\\patterns{ a12b }").unwrap_err(),
+            PatternError::InvalidPattern("a12b".to_owned())
+        );
+        assert_eq!(
+            PatternSet::parse("\\patterns{ 1 }").unwrap_err(),
+            PatternError::InvalidPattern("1".to_owned())
+        );
+    }
+}
@@ -4,4 +4,43 @@ use acryl_core::{math::Pt, Orientation};
 pub struct LayoutContext {
     pub orientation: Orientation,
     pub max_cross: Pt,
-}
\ No newline at end of file
+    /// Space left along the flow before a node has to be split, `None` if nodes can grow freely.
+    pub max_main: Option<Pt>,
+    /// Size of the enclosing container along the flow, percentages along the flow are relative to it.
+    pub container_main: Pt,
+    /// Whether nothing was placed in front of the node yet, so it has to place some content even if it
+    /// does not fit, otherwise it would never fit anywhere.
+    pub at_start: bool,
+    /// The size `em` lengths are relative to.
+    pub font_size: Pt,
+}
+
+impl LayoutContext {
+    /// A context for content that is never split, like the children of a fixed size node.
+    pub fn unbounded(
+        orientation: Orientation,
+        max_cross: Pt,
+        container_main: Pt,
+        font_size: Pt,
+    ) -> Self {
+        Self {
+            orientation,
+            max_cross,
+            max_main: None,
+            container_main,
+            at_start: true,
+            font_size,
+        }
+    }
+
+    /// Whether content of size `main` along the flow fits into [`LayoutContext::max_main`].
+    pub fn fits(&self, main: Pt) -> bool {
+        // Sums of line heights are not exact
+        const TOLERANCE: Pt = Pt(1e-6);
+
+        match self.max_main {
+            Some(max_main) => main <= max_main + TOLERANCE,
+            None => true,
+        }
+    }
+}
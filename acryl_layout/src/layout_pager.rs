@@ -1,3 +1,5 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use acryl_core::{
     math::{Area, Pt, Vector2, VectorComponent},
     Orientation,
@@ -7,18 +9,23 @@ use acryl_pdf::{stream::StreamBuilder, structure::Page};
 use crate::{
     layout_context::LayoutContext,
     node::{Node, NodePainter},
+    page_template::{Heading, Mark, PageInfo, PageRegion, PageTemplate},
     painter_context::PainterContext,
 };
 
-pub struct LayoutPager {
+pub struct LayoutPager<'a> {
     orientation: Orientation,
     page_size: Vector2<Pt>,
+    template: PageTemplate<'a>,
+    font_size: Pt,
     nodes: Vec<Node>,
 }
 
 pub struct PagePainter {
     page_size: Vector2<Pt>,
-    content: Vec<LayoutedPainter>
+    content: Vec<LayoutedPainter>,
+    labels: Vec<(String, Vector2<Pt>)>,
+    headings: Vec<Heading>,
 }
 
 struct LayoutedPainter {
@@ -26,79 +33,311 @@ struct LayoutedPainter {
     painter: Option<NodePainter>,
 }
 
-impl LayoutPager {
+/// The body of a page, before the page count is known.
+struct BodyPage {
+    content: Vec<LayoutedPainter>,
+    marks: Vec<Mark>,
+    labels: Vec<(String, Vector2<Pt>)>,
+    headings: Vec<Heading>,
+}
+
+impl<'a> LayoutPager<'a> {
+    pub const DEFAULT_FONT_SIZE: Pt = Pt(12.0);
+
     pub fn new(page_size: Vector2<Pt>) -> Self {
         let orientation = Orientation::Vertical;
 
         Self {
             orientation,
             page_size,
+            template: PageTemplate::default(),
+            font_size: Self::DEFAULT_FONT_SIZE,
             nodes: Vec::new(),
         }
     }
 
+    pub fn with_template(mut self, template: PageTemplate<'a>) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// Sets the font size `em` lengths are relative to.
+    pub fn with_font_size(mut self, font_size: Pt) -> Self {
+        self.font_size = font_size;
+        self
+    }
+
     pub fn push(&mut self, node: Node) {
         self.nodes.push(node);
     }
 
     pub fn layout(self) -> Vec<PagePainter> {
-        let ctx = LayoutContext {
-            orientation: self.orientation.clone(),
-            max_cross: self.orientation.get_cross(&self.page_size),
+        let margin = &self.template.margin;
+        let font_size = self.font_size;
+        let width = self.page_size.x - margin.horizontal();
+
+        // The regions are measured once, so that the body has the same size on every page
+        let probe = PageInfo::new(1, 1, BTreeMap::new());
+        let region_space = |region: &Option<PageRegion>| {
+            region.as_ref().map_or(Pt(0.0), |region| {
+                let height = region
+                    .height
+                    .unwrap_or_else(|| layout_region(region, &probe, width, font_size).1);
+
+                height + region.spacing
+            })
         };
 
-        let main_page_size = self.orientation.get_main(&self.page_size);
-        let mut position = Pt(0.0);
+        let header_space = region_space(&self.template.header);
+        let footer_space = region_space(&self.template.footer);
 
-        let mut pages = Vec::new();
-        let mut current_page = Vec::new();
+        let body = Area {
+            position: Vector2::new(margin.left, margin.top + header_space),
+            size: Vector2::new(
+                width,
+                self.page_size.y - margin.vertical() - header_space - footer_space,
+            ),
+        };
 
-        for node in self.nodes {
-            let result = node.layout(&ctx);
-            let size = result.size.min();
+        let body_pages = layout_body(self.nodes, self.orientation, &body, font_size);
+        let count = body_pages.len();
+
+        let mut running_marks = BTreeMap::new();
+
+        body_pages
+            .into_iter()
+            .enumerate()
+            .map(|(index, page)| {
+                let marks = page_marks(&mut running_marks, page.marks);
+                let info = PageInfo::new(index + 1, count, marks);
+
+                let mut content = page.content;
 
-            let main_size = self.orientation.get_main(size);
+                if let Some(header) = &self.template.header {
+                    let (painters, _) = layout_region(header, &info, width, font_size);
+                    let offset = Vector2::new(margin.left, margin.top);
 
-            let mut next_position = position + main_size;
+                    content.extend(painters.into_iter().map(|painter| painter.offset(&offset)));
+                }
 
-            if next_position >= main_page_size {
-                pages.push(PagePainter {
+                if let Some(footer) = &self.template.footer {
+                    let (painters, measured) = layout_region(footer, &info, width, font_size);
+                    let height = footer.height.unwrap_or(measured);
+                    let offset =
+                        Vector2::new(margin.left, self.page_size.y - margin.bottom - height);
+
+                    content.extend(painters.into_iter().map(|painter| painter.offset(&offset)));
+                }
+
+                let headings = page
+                    .headings
+                    .into_iter()
+                    .map(|heading| Heading {
+                        page: Some(index + 1),
+                        ..heading
+                    })
+                    .collect();
+
+                PagePainter {
                     page_size: self.page_size.clone(),
-                    content: current_page,
+                    content,
+                    labels: page.labels,
+                    headings,
+                }
+            })
+            .collect()
+    }
+}
+
+/// The headings of all pages, in the order of the document.
+pub fn headings(pages: &[PagePainter]) -> Vec<Heading> {
+    pages
+        .iter()
+        .flat_map(|page| page.headings.iter().cloned())
+        .collect()
+}
+
+/// Lays out the nodes into pages with a body of the size of `body`.
+fn layout_body(
+    nodes: Vec<Node>,
+    orientation: Orientation,
+    body: &Area<Pt>,
+    font_size: Pt,
+) -> Vec<BodyPage> {
+    let max_cross = orientation.get_cross(&body.size);
+    let main_page_size = orientation.get_main(&body.size);
+
+    let mut position = Pt(0.0);
+
+    let mut pages = Vec::new();
+    let mut current_page = BodyPage::new();
+
+    // Labels and headings are placed where the next node with content starts, which may be on the
+    // next page
+    let mut pending = Vec::new();
+
+    for node in nodes {
+        let mut next = Some(node);
+
+        // Nodes that are split continue on the next page until nothing is left
+        while let Some(node) = next.take() {
+            let ctx = LayoutContext {
+                orientation,
+                max_cross,
+                max_main: Some(main_page_size - position),
+                container_main: main_page_size,
+                at_start: current_page.content.is_empty(),
+                font_size,
+            };
+
+            let result = node.layout(&ctx);
+            let size = result.size.preferred();
+
+            let main_size = orientation.get_main(size);
+
+            // Nodes that can not be split move to the next page as a whole
+            if result.remainder.is_none() && !ctx.at_start && !ctx.fits(main_size) {
+                pages.push(current_page);
+
+                current_page = BodyPage::new();
+                position = Pt(0.0);
+            }
+
+            for mark in result.marks {
+                match mark {
+                    Mark::Value { .. } => current_page.marks.push(mark),
+                    mark => pending.push(mark),
+                }
+            }
+
+            if result.painter.is_some() {
+                let area = Area {
+                    position: body.position.clone()
+                        + orientation.create_vector(position, Pt::ZERO),
+                    size: size.clone(),
+                };
+
+                current_page.place(pending.drain(..), &area.position);
+
+                current_page.content.push(LayoutedPainter {
+                    area,
+                    painter: result.painter,
                 });
+            }
+
+            position += main_size;
 
-                current_page = Vec::new();
+            if let Some(remainder) = result.remainder {
+                pages.push(current_page);
+
+                current_page = BodyPage::new();
                 position = Pt(0.0);
-                next_position = main_size;
+                next = Some(remainder);
             }
+        }
+    }
 
-            let area = Area {
-                position: self.orientation.create_vector(position, Pt::ZERO),
-                size: size.clone(),
-            };
+    let end = body.position.clone() + orientation.create_vector(position, Pt::ZERO);
+    current_page.place(pending, &end);
 
-            let layout = LayoutedPainter {
-                area,
-                painter: result.painter,
-            };
+    if !current_page.content.is_empty() {
+        pages.push(current_page);
+    } else if let Some(last) = pages.last_mut() {
+        // Labels and headings after all content belong to the last page
+        last.labels.append(&mut current_page.labels);
+        last.headings.append(&mut current_page.headings);
+    }
+
+    pages
+}
+
+/// Lays out the content of a region below each other, returns the painters relative to the top left
+/// corner of the region and its height.
+fn layout_region(
+    region: &PageRegion,
+    info: &PageInfo,
+    width: Pt,
+    font_size: Pt,
+) -> (Vec<LayoutedPainter>, Pt) {
+    // Regions grow with their content, so there is no height percentages could be relative to
+    let ctx = LayoutContext::unbounded(Orientation::Vertical, width, Pt(0.0), font_size);
+
+    let mut painters = Vec::new();
+    let mut height = Pt(0.0);
 
-            current_page.push(layout);
+    for node in (region.builder)(info) {
+        let result = node.layout(&ctx);
+        let size = result.size.preferred().clone();
+
+        let area = Area {
+            position: Vector2::new(Pt(0.0), height),
+            size: size.clone(),
+        };
+
+        height += size.y;
+
+        painters.push(LayoutedPainter {
+            area,
+            painter: result.painter,
+        });
+    }
 
-            position = next_position;
+    (painters, height)
+}
+
+/// The marks of a page are the first mark of each name on the page, or the last one of an earlier page.
+fn page_marks(running: &mut BTreeMap<String, String>, marks: Vec<Mark>) -> BTreeMap<String, String> {
+    let mut page_marks = running.clone();
+    let mut seen = BTreeSet::new();
+
+    for mark in marks {
+        let Mark::Value { name, value } = mark else {
+            continue;
+        };
+
+        if seen.insert(name.clone()) {
+            page_marks.insert(name.clone(), value.clone());
         }
 
-        if !current_page.is_empty() {
-            pages.push(PagePainter {
-                page_size: self.page_size.clone(),
-                content: current_page,
-            });
+        running.insert(name, value);
+    }
+
+    page_marks
+}
+
+impl BodyPage {
+    fn new() -> Self {
+        Self {
+            content: Vec::new(),
+            marks: Vec::new(),
+            labels: Vec::new(),
+            headings: Vec::new(),
         }
+    }
 
-        pages
+    /// Records labels and headings at `position`.
+    fn place<I: IntoIterator<Item = Mark>>(&mut self, marks: I, position: &Vector2<Pt>) {
+        for mark in marks {
+            match mark {
+                Mark::Label(name) => self.labels.push((name, position.clone())),
+                Mark::Heading(heading) => self.headings.push(heading),
+                Mark::Value { .. } => self.marks.push(mark),
+            }
+        }
     }
 }
 
 impl PagePainter {
+    /// The labels on the page with the position of the top left corner of their element.
+    pub fn labels(&self) -> &[(String, Vector2<Pt>)] {
+        &self.labels
+    }
+
+    /// The headings on the page, in the order of the document.
+    pub fn headings(&self) -> &[Heading] {
+        &self.headings
+    }
+
     pub fn paint(self) -> Page {
         let mut page = Page::new(self.page_size);
         let mut stream_builder = StreamBuilder::new(&mut page);
@@ -114,6 +353,11 @@ impl PagePainter {
 }
 
 impl LayoutedPainter {
+    fn offset(mut self, offset: &Vector2<Pt>) -> Self {
+        self.area.position = self.area.position + offset.clone();
+        self
+    }
+
     fn paint(self, stream_builder: &mut StreamBuilder<'_>) {
         let mut ctx = PainterContext {
             stream_builder,
@@ -12,14 +12,14 @@ pub struct ColorBoxNode {
 }
 
 impl ColorBoxNode {
-    pub fn color<T: Into<Node>>(color: Color, child: Option<Node>) -> Self {
+    pub fn color(color: Color, child: Option<Node>) -> Self {
         Self {
             fill: Some(FillPaintArgs {
                 color,
                 fill_rule: FillRule::NonzeroWinding,
             }),
             stroke: None,
-            child: child.map(|child| Box::new(child.into())),
+            child: child.map(Box::new),
         }
     }
 }
@@ -33,9 +33,32 @@ impl From<ColorBoxNode> for Node {
 impl NodeLayout for ColorBoxNode {
     fn layout(self, ctx: &LayoutContext) -> NodeResult {
         if let Some(child) = self.child {
-            let NodeResult { size, painter } = child.layout(ctx);
+            let NodeResult {
+                size,
+                painter,
+                remainder,
+                marks,
+            } = child.layout(ctx);
+
+            // Every part of a split child gets its own box
+            let remainder = remainder.map(|child| {
+                Node::ColorBox(ColorBoxNode {
+                    fill: self.fill.clone(),
+                    stroke: self.stroke.clone(),
+                    child: Some(Box::new(child)),
+                })
+            });
+
+            if painter.is_none() && remainder.is_some() {
+                return NodeResult {
+                    remainder,
+                    ..NodeResult::default()
+                };
+            }
 
             NodeResult::new(size, ColorBoxPainter::new(self.fill, self.stroke, painter))
+                .with_remainder(remainder)
+                .with_marks(marks)
         } else {
             NodeResult::default()
         }
@@ -0,0 +1,458 @@
+use acryl_core::{
+    math::{Area, Pt, Vector2},
+    CrossAxisAlignment, Orientation,
+};
+
+use crate::{
+    dynamic_size::DySize,
+    flex_style::{FlexFit, FlexStyle},
+    layout_context::LayoutContext,
+    painter_context::PainterContext,
+};
+
+use super::{
+    flexible::FlexibleNode, node_result::NodeResult, placed::PlacedNode, Node, NodeLayout,
+    NodePaint, NodePainter,
+};
+
+/// Lays out its children one after another along `orientation`.
+pub struct FlexNode {
+    pub orientation: Orientation,
+    pub style: FlexStyle,
+    pub children: Vec<Node>,
+}
+
+impl From<FlexNode> for Node {
+    fn from(value: FlexNode) -> Self {
+        Self::Flex(value)
+    }
+}
+
+impl NodeLayout for FlexNode {
+    fn layout(self, ctx: &LayoutContext) -> NodeResult {
+        // Across the flow of its parent the main axis is bounded by `max_cross`, so the free space is
+        // distributed now. Along the flow it is only known once the flex is painted.
+        if self.orientation != ctx.orientation {
+            self.layout_across(ctx)
+        } else {
+            self.layout_along(ctx)
+        }
+    }
+}
+
+impl FlexNode {
+    fn layout_across(self, ctx: &LayoutContext) -> NodeResult {
+        let orientation = self.orientation;
+        let spacing = self.style.spacing.resolve(ctx.font_size, ctx.max_cross);
+
+        let gaps = self.children.len().saturating_sub(1) as f64;
+        let mut used = spacing * gaps;
+
+        let mut children = Vec::with_capacity(self.children.len());
+        let mut deferred = Vec::new();
+        let mut marks = Vec::new();
+
+        for (index, child) in self.children.into_iter().enumerate() {
+            if let Node::Flexible(flexible) = child {
+                deferred.push((index, flexible));
+                children.push(None);
+                continue;
+            }
+
+            // The children are side by side, so they can not be split along the flow
+            let child_ctx = LayoutContext::unbounded(
+                ctx.orientation,
+                ctx.max_cross - used,
+                ctx.container_main,
+                ctx.font_size,
+            );
+
+            let result = child.layout(&child_ctx);
+            let size = result.size.preferred().clone();
+
+            used += orientation.get_main(&size);
+            marks.extend(result.marks);
+
+            children.push(Some(FlexChild {
+                max_main: orientation.get_main(result.size.max()),
+                size,
+                flex: 0.0,
+                fit: FlexFit::Tight,
+                painter: result.painter,
+            }));
+        }
+
+        let total_flex = deferred
+            .iter()
+            .map(|(_, flexible)| flexible.flex)
+            .sum::<f64>();
+        let free = ctx.max_cross - used;
+
+        for (index, flexible) in deferred {
+            let share = if free > Pt(0.0) && total_flex > 0.0 {
+                free * (flexible.flex / total_flex)
+            } else {
+                Pt(0.0)
+            };
+
+            let child_ctx = LayoutContext::unbounded(
+                ctx.orientation,
+                share,
+                ctx.container_main,
+                ctx.font_size,
+            );
+
+            let result = flexible
+                .child
+                .map(|child| child.layout(&child_ctx))
+                .unwrap_or_default();
+            let preferred = result.size.preferred();
+
+            marks.extend(result.marks);
+
+            let main = match flexible.fit {
+                FlexFit::Tight => share,
+                FlexFit::Loose => orientation.get_main(preferred).min(share),
+            };
+
+            children[index] = Some(FlexChild {
+                size: orientation.create_vector(main, orientation.get_cross(preferred)),
+                max_main: main,
+                // The space was already distributed
+                flex: 0.0,
+                fit: flexible.fit,
+                painter: result.painter,
+            });
+        }
+
+        let children = children
+            .into_iter()
+            .map(|child| child.expect("every deferred child was laid out"))
+            .collect::<Vec<_>>();
+
+        let cross = children
+            .iter()
+            .map(|child| orientation.get_cross(&child.size))
+            .fold(Pt(0.0), Pt::max);
+
+        NodeResult::new(
+            DySize::Fixed(orientation.create_vector(ctx.max_cross, cross)),
+            FlexPainter {
+                orientation,
+                style: self.style,
+                spacing,
+                children,
+            },
+        )
+        .with_marks(marks)
+    }
+
+    /// Lays out the children one after another, until one of them does not fit into `max_main`.
+    ///
+    /// The rest of that child and all children after it are the remainder of the flex.
+    fn layout_along(self, ctx: &LayoutContext) -> NodeResult {
+        let orientation = self.orientation;
+        let gap = self
+            .style
+            .spacing
+            .resolve(ctx.font_size, ctx.container_main);
+
+        let mut children = Vec::new();
+        let mut rest = Vec::new();
+        let mut marks = Vec::new();
+        let mut used = Pt(0.0);
+
+        let mut pending = self.children.into_iter();
+
+        while let Some(child) = pending.next() {
+            let (flex, fit, child) = match child {
+                Node::Flexible(FlexibleNode { flex, fit, child }) => (flex, fit, child),
+                child => (0.0, FlexFit::Tight, Some(Box::new(child))),
+            };
+
+            let spacing = if children.is_empty() { Pt(0.0) } else { gap };
+
+            let child_ctx = LayoutContext {
+                orientation: ctx.orientation,
+                max_cross: ctx.max_cross,
+                max_main: ctx.max_main.map(|max_main| max_main - used - spacing),
+                container_main: ctx.container_main,
+                at_start: ctx.at_start && children.is_empty(),
+                font_size: ctx.font_size,
+            };
+
+            let result = child
+                .map(|child| child.layout(&child_ctx))
+                .unwrap_or_default();
+            let main = orientation.get_main(result.size.preferred());
+
+            let keep_flex = |node: Node| {
+                if flex > 0.0 {
+                    node.flexible(flex, fit)
+                } else {
+                    node
+                }
+            };
+
+            // A child that can not be split moves on as a whole
+            if result.remainder.is_none() && !child_ctx.at_start && !child_ctx.fits(main) {
+                rest.push(keep_flex(Node::Placed(PlacedNode {
+                    size: result.size,
+                    painter: result.painter,
+                    marks: result.marks,
+                })));
+                rest.extend(pending);
+                break;
+            }
+
+            // A child that was split before its first part leaves nothing to place here
+            let is_deferred = result.painter.is_none() && result.remainder.is_some();
+
+            if !is_deferred {
+                used += spacing + main;
+                marks.extend(result.marks);
+
+                children.push(FlexChild {
+                    max_main: orientation.get_main(result.size.max()),
+                    size: result.size.preferred().clone(),
+                    flex,
+                    fit,
+                    painter: result.painter,
+                });
+            }
+
+            if let Some(remainder) = result.remainder {
+                rest.push(keep_flex(remainder));
+                rest.extend(pending);
+                break;
+            }
+        }
+
+        let remainder = if rest.is_empty() {
+            None
+        } else {
+            Some(Node::Flex(FlexNode {
+                orientation,
+                style: self.style.clone(),
+                children: rest,
+            }))
+        };
+
+        if children.is_empty() {
+            if let Some(remainder) = remainder {
+                return NodeResult {
+                    remainder: Some(remainder),
+                    ..NodeResult::default()
+                };
+            }
+        }
+
+        let cross = if self.style.cross_axis_alignment == CrossAxisAlignment::Stretch {
+            ctx.max_cross
+        } else {
+            children
+                .iter()
+                .map(|child| orientation.get_cross(&child.size))
+                .fold(Pt(0.0), Pt::max)
+        };
+
+        // Flexible children share the space that is left along the flow, so the flex takes all of it
+        let size = if children.iter().any(|child| child.flex > 0.0) {
+            DySize::MixMax {
+                min: orientation.create_vector(used, cross),
+                max: orientation.create_vector(Pt(f64::INFINITY), cross),
+                preferred: ctx
+                    .max_main
+                    .map(|max_main| orientation.create_vector(max_main.max(used), cross)),
+            }
+        } else {
+            DySize::Fixed(orientation.create_vector(used, cross))
+        };
+
+        NodeResult::new(
+            size,
+            FlexPainter {
+                orientation,
+                style: self.style,
+                spacing: gap,
+                children,
+            },
+        )
+        .with_remainder(remainder)
+        .with_marks(marks)
+    }
+}
+
+struct FlexChild {
+    size: Vector2<Pt>,
+    /// Largest main size of the child, only used if it is flexible.
+    max_main: Pt,
+    flex: f64,
+    fit: FlexFit,
+    painter: Option<NodePainter>,
+}
+
+pub struct FlexPainter {
+    orientation: Orientation,
+    style: FlexStyle,
+    spacing: Pt,
+    children: Vec<FlexChild>,
+}
+
+impl From<FlexPainter> for NodePainter {
+    fn from(value: FlexPainter) -> Self {
+        Self::Flex(value)
+    }
+}
+
+impl FlexPainter {
+    /// The main sizes of the children, with the free space of `max_main` shared by the flexible ones.
+    fn main_sizes(&self, max_main: Pt) -> Vec<Pt> {
+        let orientation = self.orientation;
+
+        let mut main_sizes = self
+            .children
+            .iter()
+            .map(|child| orientation.get_main(&child.size))
+            .collect::<Vec<_>>();
+
+        let gaps = main_sizes.len().saturating_sub(1) as f64;
+        let free = main_sizes
+            .iter()
+            .fold(max_main - self.spacing * gaps, |free, main| free - *main);
+        let total_flex = self.children.iter().map(|child| child.flex).sum::<f64>();
+
+        if free > Pt(0.0) && total_flex > 0.0 {
+            for (main, child) in main_sizes.iter_mut().zip(&self.children) {
+                let grown = *main + free * (child.flex / total_flex);
+
+                *main = match child.fit {
+                    FlexFit::Tight => grown,
+                    FlexFit::Loose => grown.min(child.max_main),
+                };
+            }
+        }
+
+        main_sizes
+    }
+}
+
+impl NodePaint for FlexPainter {
+    fn paint(self, ctx: &mut PainterContext) {
+        let orientation = self.orientation;
+
+        // The children are aligned in the area the flex was given, which may be larger than its own size
+        let max_main = orientation.get_main(&ctx.area.size);
+        let max_cross = orientation.get_cross(&ctx.area.size);
+
+        let main_sizes = self.main_sizes(max_main);
+
+        let positions =
+            self.style
+                .main_axis_alignment
+                .get_positions(max_main, &main_sizes, self.spacing);
+
+        let cross_alignment = self.style.cross_axis_alignment;
+
+        for ((child, main_position), main) in
+            self.children.into_iter().zip(positions).zip(main_sizes)
+        {
+            let painter = match child.painter {
+                Some(painter) => painter,
+                None => continue,
+            };
+
+            let cross = orientation.get_cross(&child.size);
+            let cross_position = cross_alignment.get_position(max_cross, cross);
+            let cross_size = cross_alignment.get_size(max_cross, cross);
+
+            let mut child_ctx = PainterContext {
+                stream_builder: ctx.stream_builder,
+                area: Area {
+                    position: ctx.area.position.clone()
+                        + orientation.create_vector(main_position, cross_position),
+                    size: orientation.create_vector(main, cross_size),
+                },
+            };
+
+            painter.paint(&mut child_ctx);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acryl_core::{
+        math::{Mm, Pt},
+        Orientation,
+    };
+
+    use crate::{
+        flex_style::FlexStyle,
+        layout_context::LayoutContext,
+        node::{Node, NodePainter},
+    };
+
+    fn body() -> LayoutContext {
+        LayoutContext {
+            orientation: Orientation::Vertical,
+            max_cross: Pt(400.0),
+            max_main: Some(Pt(600.0)),
+            container_main: Pt(600.0),
+            at_start: true,
+            font_size: Pt(12.0),
+        }
+    }
+
+    fn column() -> Node {
+        Node::column(
+            vec![
+                Node::size(Mm(40.0), Mm(40.0)),
+                Node::size(Pt(10.0), Pt(10.0)).expanded(1.0),
+            ],
+            FlexStyle::default(),
+        )
+    }
+
+    fn main_sizes(node: Node, ctx: &LayoutContext) -> (Pt, Vec<Pt>) {
+        let result = node.layout(ctx);
+        let main = result.size.preferred().y;
+
+        match result.painter {
+            Some(NodePainter::Flex(painter)) => (main, painter.main_sizes(main)),
+            _ => panic!("a column paints as a flex"),
+        }
+    }
+
+    #[test]
+    fn expanded_child_fills_the_body() {
+        let (main, sizes) = main_sizes(column(), &body());
+
+        assert_eq!(main, Pt(600.0));
+        assert_eq!(
+            sizes,
+            vec![Pt::from(Mm(40.0)), Pt(600.0) - Pt::from(Mm(40.0))]
+        );
+    }
+
+    #[test]
+    fn expanded_child_fills_a_nested_column() {
+        let nested = Node::column(
+            vec![Node::size(Pt(50.0), Pt(50.0)), column()],
+            FlexStyle::default(),
+        );
+
+        let (main, sizes) = main_sizes(nested, &body());
+
+        assert_eq!(main, Pt(600.0));
+        assert_eq!(sizes, vec![Pt(50.0), Pt(550.0)]);
+    }
+
+    #[test]
+    fn unbounded_column_keeps_its_min_size() {
+        let ctx = LayoutContext::unbounded(Orientation::Vertical, Pt(400.0), Pt(0.0), Pt(12.0));
+        let (main, _) = main_sizes(column(), &ctx);
+
+        assert_eq!(main, Pt::from(Mm(40.0)) + Pt(10.0));
+    }
+}
@@ -0,0 +1,50 @@
+use acryl_core::math::{Pt, Vector2};
+
+use crate::{dynamic_size::DySize, flex_style::FlexFit, layout_context::LayoutContext};
+
+use super::{node_result::NodeResult, Node, NodeLayout};
+
+/// A child of a flex node that takes a share of its free space, weighted by `flex`.
+///
+/// Outside of a flex node only the child is laid out.
+pub struct FlexibleNode {
+    pub flex: f64,
+    pub fit: FlexFit,
+    pub child: Option<Box<Node>>,
+}
+
+impl From<FlexibleNode> for Node {
+    fn from(value: FlexibleNode) -> Self {
+        Self::Flexible(value)
+    }
+}
+
+impl NodeLayout for FlexibleNode {
+    fn layout(self, ctx: &LayoutContext) -> NodeResult {
+        match self.child {
+            Some(child) => {
+                let result = child.layout(ctx);
+                let remainder = result
+                    .remainder
+                    .map(|child| child.flexible(self.flex, self.fit));
+
+                NodeResult {
+                    remainder,
+                    ..result
+                }
+            }
+            None => NodeResult {
+                size: DySize::MixMax {
+                    min: Vector2::ZERO,
+                    max: ctx
+                        .orientation
+                        .create_vector(Pt(f64::INFINITY), ctx.max_cross),
+                    preferred: None,
+                },
+                painter: None,
+                remainder: None,
+                marks: Vec::new(),
+            },
+        }
+    }
+}
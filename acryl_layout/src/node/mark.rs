@@ -0,0 +1,23 @@
+use crate::{layout_context::LayoutContext, page_template::Mark};
+
+use super::{node_result::NodeResult, Node, NodeLayout};
+
+/// Sets a mark on the page it is placed on, without taking any space.
+pub struct MarkNode {
+    pub mark: Mark,
+}
+
+impl From<MarkNode> for Node {
+    fn from(value: MarkNode) -> Self {
+        Self::Mark(value)
+    }
+}
+
+impl NodeLayout for MarkNode {
+    fn layout(self, _ctx: &LayoutContext) -> NodeResult {
+        NodeResult {
+            marks: vec![self.mark],
+            ..NodeResult::default()
+        }
+    }
+}
@@ -1,9 +1,17 @@
 mod node;
 mod painter;
 mod color_box;
+mod flex;
+mod flexible;
+mod mark;
 mod node_result;
 mod padding;
+mod page_break;
+mod paragraph;
+mod placed;
 mod size_node;
+mod text;
+mod toc;
 
 pub use node::Node;
 pub use painter::NodePainter;
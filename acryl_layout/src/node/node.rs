@@ -1,36 +1,213 @@
+use std::borrow::Cow;
+
 use acryl_core::{
-    math::{Pt, Vector2},
-    Color,
+    math::{Length, Pt},
+    Color, Orientation,
+};
+use acryl_pdf::{
+    font::FontRef,
+    stream::{FillPaintArgs, StrokePaintArgs},
 };
-use acryl_pdf::stream::{FillPaintArgs, StrokePaintArgs};
 
-use crate::{layout_context::LayoutContext, padding_values::PaddingValues};
+use crate::{
+    flex_style::{FlexFit, FlexStyle},
+    layout_context::LayoutContext,
+    padding_values::PaddingValues,
+    page_template::{Heading, Mark},
+    paragraph::{ParagraphStyle, TextRun},
+};
 
 use super::{
-    color_box::ColorBoxNode, node_result::NodeResult, padding::PaddingNode,
-    size_node::SizeNode, NodeLayout,
+    color_box::ColorBoxNode,
+    flex::FlexNode,
+    flexible::FlexibleNode,
+    mark::MarkNode,
+    node_result::NodeResult,
+    padding::PaddingNode,
+    page_break::PageBreakNode,
+    paragraph::{ParagraphContent, ParagraphNode},
+    placed::PlacedNode,
+    size_node::SizeNode,
+    text::TextNode,
+    toc::TocNode,
+    NodeLayout,
 };
 
 pub enum Node {
     ColorBox(ColorBoxNode),
+    Flex(FlexNode),
+    Flexible(FlexibleNode),
+    Mark(MarkNode),
     Padding(PaddingNode),
+    PageBreak(PageBreakNode),
+    Paragraph(ParagraphNode),
+    Placed(PlacedNode),
     Size(SizeNode),
+    Text(TextNode),
+    Toc(TocNode),
 }
 
 impl Node {
     pub fn layout(self, ctx: &LayoutContext) -> NodeResult {
         match self {
             Node::ColorBox(node) => node.layout(ctx),
+            Node::Flex(node) => node.layout(ctx),
+            Node::Flexible(node) => node.layout(ctx),
+            Node::Mark(node) => node.layout(ctx),
             Node::Padding(node) => node.layout(ctx),
+            Node::PageBreak(node) => node.layout(ctx),
+            Node::Paragraph(node) => node.layout(ctx),
+            Node::Placed(node) => node.layout(ctx),
             Node::Size(node) => node.layout(ctx),
+            Node::Text(node) => node.layout(ctx),
+            Node::Toc(node) => node.layout(ctx),
+        }
+    }
+
+    /// The text of the node and its children, with the words separated by spaces.
+    pub fn plain_text(&self) -> String {
+        let mut words = Vec::new();
+        self.collect_words(&mut words);
+
+        words.join(" ")
+    }
+
+    fn collect_words<'a>(&'a self, words: &mut Vec<Cow<'a, str>>) {
+        let child = match self {
+            Node::ColorBox(node) => node.child.as_deref(),
+            Node::Flexible(node) => node.child.as_deref(),
+            Node::Padding(node) => node.child.as_deref(),
+            Node::Size(node) => node.child.as_deref(),
+            Node::Flex(node) => {
+                for child in &node.children {
+                    child.collect_words(words);
+                }
+
+                None
+            }
+            Node::Paragraph(node) => {
+                // Split paragraphs are already shaped, their text is only known to the font
+                if let ParagraphContent::Runs(runs) = &node.content {
+                    for run in runs {
+                        for (index, word) in run.words.iter().enumerate() {
+                            match words.last_mut() {
+                                Some(last) if index == 0 && run.attached => {
+                                    last.to_mut().push_str(word)
+                                }
+                                _ => words.push(Cow::Borrowed(word)),
+                            }
+                        }
+                    }
+                }
+
+                None
+            }
+            Node::Text(node) => {
+                words.push(Cow::Borrowed(&node.text));
+                None
+            }
+            Node::Mark(_) | Node::PageBreak(_) | Node::Placed(_) | Node::Toc(_) => None,
+        };
+
+        if let Some(child) = child {
+            child.collect_words(words);
         }
     }
 }
 
 impl Node {
-    pub fn size<T: Into<Pt>>(x: T, y: T) -> Self {
+    pub fn size<T: Into<Length>>(x: T, y: T) -> Self {
         Self::Size(SizeNode {
-            size: Vector2::new(x.into(), y.into()),
+            width: x.into(),
+            height: y.into(),
+            child: None,
+        })
+    }
+
+    pub fn text<T: Into<String>>(text: T, font: FontRef, font_size: f64) -> Self {
+        Self::Text(TextNode {
+            text: text.into(),
+            font,
+            font_size,
+        })
+    }
+
+    /// A paragraph of words in the text style of `style`.
+    pub fn paragraph(words: Vec<String>, style: ParagraphStyle) -> Self {
+        let run = TextRun {
+            style: style.text.clone(),
+            words,
+            attached: false,
+        };
+
+        Self::runs(vec![run], style)
+    }
+
+    /// A paragraph of runs of text, which can have different styles.
+    pub fn runs(runs: Vec<TextRun>, style: ParagraphStyle) -> Self {
+        Self::Paragraph(ParagraphNode {
+            content: ParagraphContent::Runs(runs),
+            style,
+        })
+    }
+
+    pub fn flex(orientation: Orientation, children: Vec<Node>, style: FlexStyle) -> Self {
+        Self::Flex(FlexNode {
+            orientation,
+            style,
+            children,
+        })
+    }
+
+    pub fn row(children: Vec<Node>, style: FlexStyle) -> Self {
+        Self::flex(Orientation::Horizontal, children, style)
+    }
+
+    pub fn column(children: Vec<Node>, style: FlexStyle) -> Self {
+        Self::flex(Orientation::Vertical, children, style)
+    }
+
+    pub fn mark<N: Into<String>, V: Into<String>>(name: N, value: V) -> Self {
+        Self::Mark(MarkNode {
+            mark: Mark::Value {
+                name: name.into(),
+                value: value.into(),
+            },
+        })
+    }
+
+    /// Records the page of the node after it as the page of `heading`.
+    pub fn heading(heading: Heading) -> Self {
+        Self::Mark(MarkNode {
+            mark: Mark::Heading(heading),
+        })
+    }
+
+    /// A table of contents with `entries`, which are indented by `indent` for every level.
+    pub fn toc(entries: Vec<Heading>, style: ParagraphStyle, indent: Pt) -> Self {
+        Self::Toc(TocNode {
+            entries,
+            style,
+            indent,
+        })
+    }
+
+    /// Records the page and position of the node after it under `name`.
+    pub fn label<N: Into<String>>(name: N) -> Self {
+        Self::Mark(MarkNode {
+            mark: Mark::Label(name.into()),
+        })
+    }
+
+    pub fn page_break() -> Self {
+        Self::PageBreak(PageBreakNode)
+    }
+
+    /// Empty space that takes a share of the free space of a flex node.
+    pub fn spacer(flex: f64) -> Self {
+        Self::Flexible(FlexibleNode {
+            flex,
+            fit: FlexFit::Tight,
             child: None,
         })
     }
@@ -38,7 +215,7 @@ impl Node {
 
 impl Node {
     pub fn with_color(self, color: Color) -> Self {
-        Self::ColorBox(ColorBoxNode::color::<Node>(color, Some(self)))
+        Self::ColorBox(ColorBoxNode::color(color, Some(self)))
     }
 
     pub fn with_color_box(
@@ -53,10 +230,31 @@ impl Node {
         })
     }
 
-    pub fn with_padding(self, padding: PaddingValues<Pt>) -> Self {
+    pub fn with_padding(self, padding: PaddingValues<Length>) -> Self {
         Self::Padding(PaddingNode {
             padding,
             child: Some(Box::new(self)),
         })
     }
+
+    /// Makes the node fill a share of the free space of the flex node it is a child of.
+    pub fn expanded(self, flex: f64) -> Self {
+        self.flexible(flex, FlexFit::Tight)
+    }
+
+    pub fn flexible(self, flex: f64, fit: FlexFit) -> Self {
+        Self::Flexible(FlexibleNode {
+            flex,
+            fit,
+            child: Some(Box::new(self)),
+        })
+    }
+
+    pub fn with_size<T: Into<Length>>(self, x: T, y: T) -> Self {
+        Self::Size(SizeNode {
+            width: x.into(),
+            height: y.into(),
+            child: Some(Box::new(self)),
+        })
+    }
 }
@@ -1,13 +1,18 @@
 use acryl_core::math::Pt;
 
-use crate::dynamic_size::DySize;
+use crate::{dynamic_size::DySize, page_template::Mark};
 
-use super::NodePainter;
+use super::{Node, NodePainter};
 
 #[derive(Default)]
 pub struct NodeResult {
     pub size: DySize<Pt>,
     pub painter: Option<NodePainter>,
+    /// The content that did not fit into [`LayoutContext::max_main`](crate::layout_context::LayoutContext::max_main),
+    /// which is laid out again in the next area.
+    pub remainder: Option<Node>,
+    /// Marks of the node and all of its children that were placed.
+    pub marks: Vec<Mark>,
 }
 
 impl NodeResult {
@@ -15,6 +20,8 @@ impl NodeResult {
         Self {
             size,
             painter: Some(painter.into()),
+            remainder: None,
+            marks: Vec::new(),
         }
     }
 
@@ -22,6 +29,28 @@ impl NodeResult {
         Self {
             size,
             painter: painter.map(Into::into),
+            remainder: None,
+            marks: Vec::new(),
         }
     }
+
+    /// A result that places nothing and leaves all content for the next area.
+    pub fn deferred<T: Into<Node>>(node: T) -> Self {
+        Self {
+            size: DySize::default(),
+            painter: None,
+            remainder: Some(node.into()),
+            marks: Vec::new(),
+        }
+    }
+
+    pub fn with_remainder(mut self, remainder: Option<Node>) -> Self {
+        self.remainder = remainder;
+        self
+    }
+
+    pub fn with_marks(mut self, marks: Vec<Mark>) -> Self {
+        self.marks = marks;
+        self
+    }
 }
@@ -1,4 +1,4 @@
-use acryl_core::math::Pt;
+use acryl_core::math::{Length, Pt};
 
 use crate::{
     dynamic_size::DySize, layout_context::LayoutContext, padding_values::PaddingValues,
@@ -8,7 +8,8 @@ use crate::{
 use super::{node_result::NodeResult, Node, NodeLayout, NodePaint, NodePainter};
 
 pub struct PaddingNode {
-    pub padding: PaddingValues<Pt>,
+    /// Percentages are relative to the cross size of the flow.
+    pub padding: PaddingValues<Length>,
     pub child: Option<Box<Node>>,
 }
 
@@ -20,22 +21,48 @@ impl From<PaddingNode> for Node {
 
 impl NodeLayout for PaddingNode {
     fn layout(self, ctx: &LayoutContext) -> NodeResult {
+        let padding = self.padding.resolve(ctx.font_size, ctx.max_cross);
+
         if let Some(child) = self.child {
-            let padding_vec = self.padding.vec();
+            let padding_vec = padding.vec();
 
             let ctx = LayoutContext {
                 orientation: ctx.orientation,
                 max_cross: ctx.max_cross - ctx.orientation.get_cross(&padding_vec),
+                max_main: ctx
+                    .max_main
+                    .map(|max_main| max_main - ctx.orientation.get_main(&padding_vec)),
+                container_main: ctx.container_main,
+                at_start: ctx.at_start,
+                font_size: ctx.font_size,
             };
 
-            let NodeResult { size, painter } = child.layout(&ctx);
+            let NodeResult {
+                size,
+                painter,
+                remainder,
+                marks,
+            } = child.layout(&ctx);
+
+            // Every part of a split child is padded
+            let remainder = remainder.map(|child| child.with_padding(self.padding.clone()));
 
+            if painter.is_none() && remainder.is_some() {
+                return NodeResult {
+                    remainder,
+                    ..NodeResult::default()
+                };
+            }
 
-            NodeResult::new_opt(size, PaddingPainter::new_opt(self.padding, painter))
+            NodeResult::new_opt(size + padding_vec, PaddingPainter::new_opt(padding, painter))
+                .with_remainder(remainder)
+                .with_marks(marks)
         } else {
             NodeResult {
-                size: DySize::Fixed(self.padding.vec()),
+                size: DySize::Fixed(padding.vec()),
                 painter: None,
+                remainder: None,
+                marks: Vec::new(),
             }
         }
     }
@@ -0,0 +1,22 @@
+use crate::layout_context::LayoutContext;
+
+use super::{node_result::NodeResult, Node, NodeLayout};
+
+/// Moves the content after it to the next page, unless nothing was placed on the current page yet.
+pub struct PageBreakNode;
+
+impl From<PageBreakNode> for Node {
+    fn from(value: PageBreakNode) -> Self {
+        Self::PageBreak(value)
+    }
+}
+
+impl NodeLayout for PageBreakNode {
+    fn layout(self, ctx: &LayoutContext) -> NodeResult {
+        if ctx.at_start {
+            NodeResult::default()
+        } else {
+            NodeResult::deferred(self)
+        }
+    }
+}
@@ -1,17 +1,28 @@
 use crate::painter_context::PainterContext;
 
-use super::{color_box::ColorBoxPainter, padding::PaddingPainter, NodePaint};
+use super::{
+    color_box::ColorBoxPainter, flex::FlexPainter, padding::PaddingPainter,
+    paragraph::ParagraphPainter, text::TextPainter, toc::TocPainter, NodePaint,
+};
 
 pub enum NodePainter {
     ColorBox(ColorBoxPainter),
+    Flex(FlexPainter),
     Padding(PaddingPainter),
+    Paragraph(ParagraphPainter),
+    Text(TextPainter),
+    Toc(TocPainter),
 }
 
 impl NodePainter {
     pub fn paint(self, ctx: &mut PainterContext) {
         match self {
             NodePainter::ColorBox(painter) => painter.paint(ctx),
+            NodePainter::Flex(painter) => painter.paint(ctx),
             NodePainter::Padding(painter) => painter.paint(ctx),
+            NodePainter::Paragraph(painter) => painter.paint(ctx),
+            NodePainter::Text(painter) => painter.paint(ctx),
+            NodePainter::Toc(painter) => painter.paint(ctx),
         }
     }
 }
@@ -0,0 +1,516 @@
+use acryl_core::{
+    math::{Pt, Vector2},
+    TextAlign,
+};
+use acryl_pdf::stream::PositionedGlyph;
+
+use crate::{
+    dynamic_size::DySize,
+    hyphenation::{Hyphenation, DEFAULT_HYPHEN_PENALTY},
+    layout_context::LayoutContext,
+    painter_context::PainterContext,
+    paragraph::{
+        break_lines, Item, ParagraphStyle, TextRun, TextStyle, INFINITE_PENALTY,
+        INFINITE_STRETCH,
+    },
+    shaping::{ShapedText, Shaper},
+};
+
+use super::{node_result::NodeResult, Node, NodeLayout, NodePaint, NodePainter};
+
+const SOFT_HYPHEN: char = '\u{ad}';
+
+pub struct ParagraphNode {
+    pub content: ParagraphContent,
+    pub style: ParagraphStyle,
+}
+
+pub enum ParagraphContent {
+    Runs(Vec<TextRun>),
+    /// The rest of a paragraph that was split, which is already shaped.
+    Items {
+        styles: Vec<TextStyle>,
+        items: Vec<Item<StyledGlyphs>>,
+    },
+}
+
+/// Shaped glyphs with the index of their style in the styles of the paragraph.
+pub struct StyledGlyphs {
+    style: usize,
+    glyphs: Vec<PositionedGlyph>,
+}
+
+/// A style of a paragraph with its shaper and the glyphs of a space and a hyphen.
+struct StyleFont<'a> {
+    shaper: Shaper<'a>,
+    space: ShapedText,
+    hyphen: ShapedText,
+    ascender: Pt,
+    /// The height of a line below the baseline.
+    below: Pt,
+}
+
+impl<'a> StyleFont<'a> {
+    fn new(style: &'a TextStyle) -> Self {
+        let font = style.font.font();
+        let shaper = Shaper::new(font, style.font_size);
+        let metrics = font.metrics();
+        let ascender = metrics.ascender(style.font_size);
+
+        Self {
+            space: shaper.shape(" "),
+            hyphen: shaper.shape("-"),
+            shaper,
+            ascender,
+            below: metrics.height(style.font_size) - ascender,
+        }
+    }
+}
+
+impl From<ParagraphNode> for Node {
+    fn from(value: ParagraphNode) -> Self {
+        Self::Paragraph(value)
+    }
+}
+
+impl NodeLayout for ParagraphNode {
+    fn layout(self, ctx: &LayoutContext) -> NodeResult {
+        let styles = match &self.content {
+            ParagraphContent::Runs(runs) => runs.iter().map(|run| run.style.clone()).collect(),
+            ParagraphContent::Items { styles, .. } => styles.clone(),
+        };
+        let fonts = styles.iter().map(StyleFont::new).collect::<Vec<_>>();
+
+        let mut items = match self.content {
+            ParagraphContent::Runs(runs) => {
+                create_items(&runs, &fonts, self.style.hyphenation.as_ref())
+            }
+            ParagraphContent::Items { items, .. } => items,
+        };
+
+        let line_width = ctx.max_cross;
+        let lines = break_lines(&items, line_width, self.style.line_breaking);
+        let line_count = lines.len();
+
+        let mut painted_lines = lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| {
+                let line_items = &items[line.start..=line.end];
+                let is_last_line = index + 1 == line_count;
+
+                let justify = self.style.align.is_justified(is_last_line);
+
+                let ratio = if justify {
+                    if is_last_line {
+                        // The breaker measured the last line including the glue that fills it
+                        justified_ratio(line_items, line_width)
+                    } else {
+                        line.ratio
+                    }
+                } else {
+                    0.0
+                };
+
+                PaintedLine::new(
+                    line_items,
+                    &fonts,
+                    justify,
+                    ratio,
+                    line_width,
+                    self.style.align,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // Only as many lines as fit are placed, but at least one if nothing is in front of the paragraph
+        let mut height = Pt(0.0);
+        let fitting = painted_lines
+            .iter()
+            .take_while(|line| {
+                height += line.height;
+                ctx.fits(height)
+            })
+            .count();
+        let fitting = if ctx.at_start {
+            fitting.max(1)
+        } else {
+            fitting
+        };
+
+        if fitting == 0 {
+            return NodeResult::deferred(ParagraphNode {
+                content: ParagraphContent::Items { styles, items },
+                style: self.style,
+            });
+        }
+
+        painted_lines.truncate(fitting);
+
+        let height = painted_lines
+            .iter()
+            .map(|line| line.height)
+            .fold(Pt(0.0), |sum, height| sum + height);
+
+        let remainder = if fitting < line_count {
+            let start = Item::line_start(&items, lines[fitting - 1].end);
+
+            Some(Node::Paragraph(ParagraphNode {
+                content: ParagraphContent::Items {
+                    styles: styles.clone(),
+                    items: items.split_off(start),
+                },
+                style: self.style.clone(),
+            }))
+        } else {
+            None
+        };
+
+        NodeResult::new(
+            DySize::Fixed(Vector2::new(line_width, height)),
+            ParagraphPainter {
+                styles,
+                lines: painted_lines,
+            },
+        )
+        .with_remainder(remainder)
+    }
+}
+
+/// Breaks the words into boxes, with glue between the words and penalties at which they may be hyphenated.
+///
+/// The glue after a word is as wide as a space in the style of the word.
+fn create_items(
+    runs: &[TextRun],
+    fonts: &[StyleFont],
+    hyphenation: Option<&Hyphenation>,
+) -> Vec<Item<StyledGlyphs>> {
+    let hyphen_penalty =
+        hyphenation.map_or(DEFAULT_HYPHEN_PENALTY, |hyphenation| hyphenation.penalty);
+
+    let mut items = Vec::new();
+    let mut last_style: Option<usize> = None;
+
+    for (style, (run, font)) in runs.iter().zip(fonts).enumerate() {
+        for (index, word) in run.words.iter().enumerate() {
+            let attached = index == 0 && run.attached;
+
+            if let (Some(last_style), false) = (last_style, attached) {
+                let space = &fonts[last_style].space;
+
+                items.push(Item::Glue {
+                    width: space.width,
+                    stretch: space.width * 0.5,
+                    shrink: space.width * (1.0 / 3.0),
+                });
+            }
+
+            for (index, part) in hyphenation_parts(word, hyphenation).into_iter().enumerate() {
+                if index > 0 {
+                    items.push(Item::Penalty {
+                        width: font.hyphen.width,
+                        penalty: hyphen_penalty,
+                        flagged: true,
+                    });
+                }
+
+                let shaped = font.shaper.shape(part);
+
+                items.push(Item::Box {
+                    width: shaped.width,
+                    content: StyledGlyphs {
+                        style,
+                        glyphs: shaped.glyphs,
+                    },
+                });
+            }
+
+            last_style = Some(style);
+        }
+    }
+
+    items.push(Item::Glue {
+        width: Pt(0.0),
+        stretch: INFINITE_STRETCH,
+        shrink: Pt(0.0),
+    });
+    items.push(Item::Penalty {
+        width: Pt(0.0),
+        penalty: -INFINITE_PENALTY,
+        flagged: false,
+    });
+
+    items
+}
+
+/// Splits a word into the parts between which it may be hyphenated.
+///
+/// Soft hyphens in the word take precedence over the hyphenation patterns.
+fn hyphenation_parts<'a>(word: &'a str, hyphenation: Option<&Hyphenation>) -> Vec<&'a str> {
+    if word.contains(SOFT_HYPHEN) {
+        return word
+            .split(SOFT_HYPHEN)
+            .filter(|part| !part.is_empty())
+            .collect();
+    }
+
+    let hyphenation = match hyphenation {
+        Some(hyphenation) => hyphenation,
+        None => return vec![word],
+    };
+
+    let mut parts = Vec::new();
+    let mut start = 0;
+
+    for offset in hyphenation.hyphenator.hyphenate(word) {
+        parts.push(&word[start..offset]);
+        start = offset;
+    }
+
+    parts.push(&word[start..]);
+    parts
+}
+
+/// Natural width, stretch and shrink of the items of a line, ignoring the glue that fills the last line.
+fn line_dimensions<T>(items: &[Item<T>]) -> (Pt, Pt, Pt) {
+    let (mut width, mut stretch, mut shrink) = (Pt(0.0), Pt(0.0), Pt(0.0));
+
+    let (last, items) = items.split_last().expect("a line ends with a breakpoint");
+
+    for item in items {
+        match item {
+            Item::Glue {
+                stretch: glue_stretch,
+                ..
+            } if *glue_stretch >= INFINITE_STRETCH => {}
+            Item::Glue {
+                width: glue_width,
+                stretch: glue_stretch,
+                shrink: glue_shrink,
+            } => {
+                width += *glue_width;
+                stretch += *glue_stretch;
+                shrink += *glue_shrink;
+            }
+            item => width += item.width(),
+        }
+    }
+
+    if let Item::Penalty {
+        width: penalty_width,
+        ..
+    } = last
+    {
+        width += *penalty_width;
+    }
+
+    (width, stretch, shrink)
+}
+
+fn justified_ratio<T>(items: &[Item<T>], line_width: Pt) -> f64 {
+    let (width, stretch, shrink) = line_dimensions(items);
+
+    if width < line_width && stretch > Pt(0.0) {
+        ((line_width - width) / stretch).0
+    } else if width > line_width && shrink > Pt(0.0) {
+        ((line_width - width) / shrink).0
+    } else {
+        0.0
+    }
+}
+
+struct PaintedLine {
+    offset: Pt,
+    /// Extra space after every glyph, used to justify lines without any glue.
+    char_spacing: Pt,
+    /// The glyphs of the line by their style, in the order they are painted in.
+    runs: Vec<StyledGlyphs>,
+    /// The distance from the top of the line to the baseline all runs share.
+    ascender: Pt,
+    height: Pt,
+}
+
+impl PaintedLine {
+    /// `items` are the items of the line including the breakpoint it ends at.
+    fn new(
+        items: &[Item<StyledGlyphs>],
+        fonts: &[StyleFont],
+        justify: bool,
+        ratio: f64,
+        line_width: Pt,
+        align: TextAlign,
+    ) -> Self {
+        // Overfull lines can not shrink more than their glue allows
+        let ratio = ratio.max(-1.0);
+
+        let (last, content) = items.split_last().expect("a line ends with a breakpoint");
+
+        let mut runs: Vec<StyledGlyphs> = Vec::new();
+        let mut width = Pt(0.0);
+
+        for item in content {
+            match item {
+                Item::Box {
+                    content,
+                    width: box_width,
+                } => {
+                    match runs.last_mut() {
+                        Some(run) if run.style == content.style => {
+                            run.glyphs.extend_from_slice(&content.glyphs)
+                        }
+                        _ => runs.push(StyledGlyphs {
+                            style: content.style,
+                            glyphs: content.glyphs.clone(),
+                        }),
+                    }
+
+                    width += *box_width;
+                }
+                Item::Glue { stretch, .. } if *stretch >= INFINITE_STRETCH => {}
+                Item::Glue {
+                    width: glue_width,
+                    stretch,
+                    shrink,
+                } => {
+                    let adjusted = if ratio >= 0.0 {
+                        *glue_width + *stretch * ratio
+                    } else {
+                        *glue_width + *shrink * ratio
+                    };
+
+                    // Glue always follows a box, the space is in the style of the word before it
+                    if let Some(run) = runs.last_mut() {
+                        run.glyphs.extend_from_slice(&fonts[run.style].space.glyphs);
+
+                        // The space is widened through its advance instead of the word spacing
+                        // operator `Tw`, which only applies to the single byte code 32 and so
+                        // has no effect on the two byte codes of Identity-H fonts
+                        if let Some(last) = run.glyphs.last_mut() {
+                            last.advance += adjusted - *glue_width;
+                        }
+                    }
+
+                    width += adjusted;
+                }
+                Item::Penalty { .. } => {}
+            }
+        }
+
+        // Only hyphens have a width when the line is broken at them
+        if let Item::Penalty {
+            width: hyphen_width,
+            ..
+        } = last
+        {
+            if let (Some(run), true) = (runs.last_mut(), *hyphen_width > Pt(0.0)) {
+                run.glyphs.extend_from_slice(&fonts[run.style].hyphen.glyphs);
+                width += *hyphen_width;
+            }
+        }
+
+        let has_glue = content
+            .iter()
+            .any(|item| matches!(item, Item::Glue { stretch, .. } if *stretch < INFINITE_STRETCH));
+        let glyph_count = runs.iter().map(|run| run.glyphs.len()).sum::<usize>();
+
+        let char_spacing = if justify && !has_glue && glyph_count > 1 {
+            let spacing = (line_width - width) * (1.0 / (glyph_count - 1) as f64);
+            width = line_width;
+            spacing
+        } else {
+            Pt(0.0)
+        };
+
+        let offset = if justify {
+            Pt(0.0)
+        } else {
+            align.get_offset(line_width, width)
+        };
+
+        // The runs share a baseline, which is as low as the tallest of them needs it
+        let ascender = runs
+            .iter()
+            .map(|run| fonts[run.style].ascender)
+            .fold(Pt(0.0), Pt::max);
+        let below = runs
+            .iter()
+            .map(|run| fonts[run.style].below)
+            .fold(Pt(0.0), Pt::max);
+
+        Self {
+            offset,
+            char_spacing,
+            runs,
+            ascender,
+            height: ascender + below,
+        }
+    }
+}
+
+pub struct ParagraphPainter {
+    styles: Vec<TextStyle>,
+    lines: Vec<PaintedLine>,
+}
+
+impl From<ParagraphPainter> for NodePainter {
+    fn from(value: ParagraphPainter) -> Self {
+        Self::Paragraph(value)
+    }
+}
+
+impl NodePaint for ParagraphPainter {
+    fn paint(self, ctx: &mut PainterContext) {
+        let mut position = ctx.area.position.clone();
+
+        for line in self.lines {
+            let mut run_position = position.clone();
+            run_position.x += line.offset;
+
+            for run in &line.runs {
+                let style = &self.styles[run.style];
+
+                // The color is part of the graphics state, which is restored so it does not leak
+                // into other runs
+                if let Some(color) = &style.color {
+                    ctx.stream_builder.save_state();
+                    ctx.stream_builder.set_fill_color(color.clone());
+                }
+
+                let mut text = ctx.stream_builder.text(&style.font, style.font_size);
+
+                // The text is placed by the top of its font, which is moved down to the baseline
+                // of the line
+                let ascender = style.font.font().metrics().ascender(style.font_size);
+                let mut top = run_position.clone();
+                top.y += line.ascender - ascender;
+
+                text.set_position(top);
+
+                if line.char_spacing != Pt(0.0) {
+                    text.set_char_spacing(line.char_spacing);
+                }
+
+                text.draw_glyphs(&run.glyphs);
+
+                // Char spacing is part of the text state, which outlives the text object
+                if line.char_spacing != Pt(0.0) {
+                    text.set_char_spacing(Pt(0.0));
+                }
+
+                drop(text);
+
+                if style.color.is_some() {
+                    ctx.stream_builder.restore_state();
+                }
+
+                run_position.x += run
+                    .glyphs
+                    .iter()
+                    .map(|glyph| glyph.advance + line.char_spacing)
+                    .fold(Pt(0.0), |sum, advance| sum + advance);
+            }
+
+            position.y += line.height;
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use acryl_core::math::Pt;
+
+use crate::{dynamic_size::DySize, layout_context::LayoutContext, page_template::Mark};
+
+use super::{node_result::NodeResult, Node, NodeLayout, NodePainter};
+
+/// A node that was already laid out, but did not fit and was moved to the next area as a whole.
+///
+/// The layout is reused as is, so the next area has to be as wide as the one it was laid out for.
+pub struct PlacedNode {
+    pub size: DySize<Pt>,
+    pub painter: Option<NodePainter>,
+    pub marks: Vec<Mark>,
+}
+
+impl From<PlacedNode> for Node {
+    fn from(value: PlacedNode) -> Self {
+        Self::Placed(value)
+    }
+}
+
+impl NodeLayout for PlacedNode {
+    fn layout(self, _ctx: &LayoutContext) -> NodeResult {
+        NodeResult {
+            size: self.size,
+            painter: self.painter,
+            remainder: None,
+            marks: self.marks,
+        }
+    }
+}
@@ -1,29 +1,85 @@
-use acryl_core::math::{Pt, Vector2};
+use acryl_core::math::{Length, Vector2};
 
 use crate::{dynamic_size::DySize, layout_context::LayoutContext};
 
 use super::{node_result::NodeResult, Node, NodeLayout};
 
 
+/// A node of a fixed size, percentages of the width and height are relative to the size of the
+/// container the node is placed in.
 pub struct SizeNode {
-    pub size: Vector2<Pt>,
+    pub width: Length,
+    pub height: Length,
     pub child: Option<Box<Node>>
 }
 
 impl NodeLayout for SizeNode {
     fn layout(self, ctx: &LayoutContext) -> NodeResult {
-        let ctx = LayoutContext {
-            orientation: ctx.orientation,
-            max_cross: ctx.orientation.get_cross(&self.size),
-        };
+        let container = ctx
+            .orientation
+            .create_vector(ctx.container_main, ctx.max_cross);
+        let size = Vector2::new(
+            self.width.resolve(ctx.font_size, container.x),
+            self.height.resolve(ctx.font_size, container.y),
+        );
 
-        let painter = self.child
+        // The size is fixed, so the child is never split
+        let ctx = LayoutContext::unbounded(
+            ctx.orientation,
+            ctx.orientation.get_cross(&size),
+            ctx.orientation.get_main(&size),
+            ctx.font_size,
+        );
+
+        let result = self.child
             .map(|child| child.layout(&ctx))
-            .and_then(|result| result.painter);
+            .unwrap_or_default();
 
         NodeResult {
-            size: DySize::Fixed(self.size),
-            painter,
+            size: DySize::Fixed(size),
+            painter: result.painter,
+            remainder: None,
+            marks: result.marks,
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use acryl_core::{
+        math::{Percent, Pt},
+        Orientation,
+    };
+
+    use crate::{layout_context::LayoutContext, node::Node};
+
+    fn body(max_main: Pt) -> LayoutContext {
+        LayoutContext {
+            orientation: Orientation::Vertical,
+            max_cross: Pt(400.0),
+            max_main: Some(max_main),
+            container_main: Pt(600.0),
+            at_start: true,
+            font_size: Pt(12.0),
         }
     }
-}
\ No newline at end of file
+
+    fn height(node: Node, ctx: &LayoutContext) -> Pt {
+        node.layout(ctx).size.preferred().y
+    }
+
+    #[test]
+    fn percentages_are_relative_to_the_container() {
+        let node = || Node::size(Percent(50.0), Percent(50.0));
+
+        assert_eq!(height(node(), &body(Pt(600.0))), Pt(300.0));
+        // Content above the node does not shrink it
+        assert_eq!(height(node(), &body(Pt(100.0))), Pt(300.0));
+    }
+
+    #[test]
+    fn unbounded_content_is_relative_to_the_given_container() {
+        let ctx = LayoutContext::unbounded(Orientation::Vertical, Pt(100.0), Pt(200.0), Pt(12.0));
+
+        assert_eq!(height(Node::size(Percent(50.0), Percent(50.0)), &ctx), Pt(100.0));
+    }
+}
@@ -0,0 +1,62 @@
+use acryl_core::math::Vector2;
+use acryl_pdf::{font::FontRef, stream::PositionedGlyph};
+
+use crate::{
+    dynamic_size::DySize, layout_context::LayoutContext, painter_context::PainterContext,
+    shaping::Shaper,
+};
+
+use super::{node_result::NodeResult, Node, NodeLayout, NodePaint, NodePainter};
+
+pub struct TextNode {
+    pub text: String,
+    pub font: FontRef,
+    pub font_size: f64,
+}
+
+impl From<TextNode> for Node {
+    fn from(value: TextNode) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl NodeLayout for TextNode {
+    fn layout(self, _ctx: &LayoutContext) -> NodeResult {
+        let font = self.font.font();
+
+        let shaped = Shaper::new(font, self.font_size).shape(&self.text);
+        let height = font.metrics().height(self.font_size);
+
+        NodeResult::new(
+            DySize::Fixed(Vector2::new(shaped.width, height)),
+            TextPainter {
+                glyphs: shaped.glyphs,
+                font: self.font,
+                font_size: self.font_size,
+            },
+        )
+    }
+}
+
+pub struct TextPainter {
+    glyphs: Vec<PositionedGlyph>,
+    font: FontRef,
+    font_size: f64,
+}
+
+impl From<TextPainter> for NodePainter {
+    fn from(value: TextPainter) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl NodePaint for TextPainter {
+    fn paint(self, ctx: &mut PainterContext) {
+        let position = ctx.area.position.clone();
+
+        let mut text = ctx.stream_builder.text(&self.font, self.font_size);
+
+        text.set_position(position);
+        text.draw_glyphs(&self.glyphs);
+    }
+}
@@ -0,0 +1,178 @@
+use acryl_core::{
+    math::{Pt, Vector2},
+    Color,
+};
+use acryl_pdf::{font::FontRef, stream::PositionedGlyph};
+
+use crate::{
+    dynamic_size::DySize, layout_context::LayoutContext, page_template::Heading,
+    painter_context::PainterContext, paragraph::ParagraphStyle, shaping::Shaper,
+};
+
+use super::{node_result::NodeResult, Node, NodeLayout, NodePaint, NodePainter};
+
+/// A table of contents with a line for every heading, the page number is aligned to the right and
+/// joined to the title with a leader of dots.
+///
+/// Titles are not broken into lines, a title that is too long leaves no room for the leader.
+pub struct TocNode {
+    pub entries: Vec<Heading>,
+    pub style: ParagraphStyle,
+    /// How far an entry is indented for every level it is below the first one.
+    pub indent: Pt,
+}
+
+/// Space between the dots of a leader, relative to the font size.
+const LEADER_SPACING: f64 = 0.5;
+
+impl From<TocNode> for Node {
+    fn from(value: TocNode) -> Self {
+        Self::Toc(value)
+    }
+}
+
+impl NodeLayout for TocNode {
+    fn layout(mut self, ctx: &LayoutContext) -> NodeResult {
+        if self.entries.is_empty() {
+            return NodeResult::default();
+        }
+
+        let font = self.style.text.font.font();
+        let font_size = self.style.text.font_size;
+
+        let shaper = Shaper::new(font, font_size);
+        let dot = shaper.shape(".");
+        let gap = shaper.shape(" ").width;
+
+        let line_height = font.metrics().height(font_size);
+        let width = ctx.max_cross;
+
+        // Only as many entries as fit are placed, but at least one if nothing is in front of them
+        let fitting = (1..=self.entries.len())
+            .take_while(|count| ctx.fits(line_height * *count as f64))
+            .count();
+        let fitting = if ctx.at_start {
+            fitting.max(1)
+        } else {
+            fitting
+        };
+
+        if fitting == 0 {
+            return NodeResult::deferred(self);
+        }
+
+        let rest = self.entries.split_off(fitting);
+
+        let lines = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let title = match &entry.number {
+                    Some(number) => format!("{} {}", number, entry.title),
+                    None => entry.title.clone(),
+                };
+
+                let title = shaper.shape(&title);
+                let page = entry.page.map(|page| page.to_string()).unwrap_or_default();
+                let page = shaper.shape(&page);
+
+                let title_start = self.indent * entry.level.saturating_sub(1) as f64;
+                let page_start = width - page.width;
+
+                // The dots are on a grid, so they line up from one entry to the next
+                let spacing = Pt(font_size * LEADER_SPACING) + dot.width;
+                let first = ((title_start + title.width + gap).0 / spacing.0).ceil() as usize;
+                let last = ((page_start - gap - dot.width).0 / spacing.0).floor() as usize;
+
+                let mut runs = vec![(title_start, title.glyphs), (page_start, page.glyphs)];
+
+                if first <= last && !dot.glyphs.is_empty() {
+                    let glyphs = (first..=last)
+                        .flat_map(|_| {
+                            dot.glyphs.iter().cloned().map(|mut glyph| {
+                                glyph.advance = spacing;
+                                glyph
+                            })
+                        })
+                        .collect();
+
+                    runs.push((spacing * first as f64, glyphs));
+                }
+
+                TocLine { runs }
+            })
+            .collect::<Vec<_>>();
+
+        let height = line_height * lines.len() as f64;
+
+        let remainder = if rest.is_empty() {
+            None
+        } else {
+            Some(Node::Toc(TocNode {
+                entries: rest,
+                style: self.style.clone(),
+                indent: self.indent,
+            }))
+        };
+
+        NodeResult::new(
+            DySize::Fixed(Vector2::new(width, height)),
+            TocPainter {
+                font: self.style.text.font,
+                font_size,
+                color: self.style.text.color,
+                line_height,
+                lines,
+            },
+        )
+        .with_remainder(remainder)
+    }
+}
+
+/// The runs of glyphs of an entry, with their offset from the left edge.
+struct TocLine {
+    runs: Vec<(Pt, Vec<PositionedGlyph>)>,
+}
+
+pub struct TocPainter {
+    font: FontRef,
+    font_size: f64,
+    color: Option<Color>,
+    line_height: Pt,
+    lines: Vec<TocLine>,
+}
+
+impl From<TocPainter> for NodePainter {
+    fn from(value: TocPainter) -> Self {
+        Self::Toc(value)
+    }
+}
+
+impl NodePaint for TocPainter {
+    fn paint(self, ctx: &mut PainterContext) {
+        let mut position = ctx.area.position.clone();
+
+        if let Some(color) = &self.color {
+            ctx.stream_builder.save_state();
+            ctx.stream_builder.set_fill_color(color.clone());
+        }
+
+        for line in self.lines {
+            for (offset, glyphs) in line.runs {
+                let mut text = ctx.stream_builder.text(&self.font, self.font_size);
+
+                let mut run_position = position.clone();
+                run_position.x += offset;
+
+                text.set_position(run_position);
+                text.draw_glyphs(&glyphs);
+            }
+
+            position.y += self.line_height;
+        }
+
+        if self.color.is_some() {
+            ctx.stream_builder.restore_state();
+        }
+    }
+}
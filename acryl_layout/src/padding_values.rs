@@ -1,14 +1,14 @@
-use acryl_core::math::{Area, Unit, Vector2, VectorComponent};
+use acryl_core::math::{Area, Length, Pt, Unit, Vector2, VectorComponent};
 
 #[derive(Debug, Clone)]
-pub struct PaddingValues<U: Unit> {
+pub struct PaddingValues<U> {
     pub top: U,
     pub bottom: U,
     pub left: U,
     pub right: U,
 }
 
-impl<U: Unit> PaddingValues<U> {
+impl<U: Copy> PaddingValues<U> {
     pub fn all(value: U) -> Self {
         Self {
             top: value,
@@ -26,7 +26,9 @@ impl<U: Unit> PaddingValues<U> {
             right: horizontal,
         }
     }
+}
 
+impl<U: Unit> PaddingValues<U> {
     pub fn vertical(&self) -> U {
         self.top + self.bottom
     }
@@ -53,3 +55,15 @@ impl<U: Unit> PaddingValues<U> {
         }
     }
 }
+
+impl PaddingValues<Length> {
+    /// Converts the padding to points, percentages are relative to `container` on all sides.
+    pub fn resolve(&self, font_size: Pt, container: Pt) -> PaddingValues<Pt> {
+        PaddingValues {
+            top: self.top.resolve(font_size, container),
+            bottom: self.bottom.resolve(font_size, container),
+            left: self.left.resolve(font_size, container),
+            right: self.right.resolve(font_size, container),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::BTreeMap;
+
+use acryl_core::math::Pt;
+
+use crate::{node::Node, padding_values::PaddingValues};
+
+/// Something that is recorded where a node is placed.
+#[derive(Debug, Clone)]
+pub enum Mark {
+    /// A named value, like the title of the current section.
+    Value { name: String, value: String },
+    /// The label of an element, which references find the page and position of.
+    Label(String),
+    /// A heading, which is listed in the table of contents.
+    Heading(Heading),
+}
+
+/// A heading of the document, which is recorded on the page it is placed on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heading {
+    /// 1 for sections, 2 for their subsections and so on.
+    pub level: usize,
+    /// The number of a numbered heading, like `2.1`.
+    pub number: Option<String>,
+    pub title: String,
+    /// The label of the call the heading is in.
+    pub label: Option<String>,
+    /// The number of the page the heading is on, which is only known after the layout.
+    pub page: Option<usize>,
+}
+
+/// Information about the page a header or footer is built for.
+#[derive(Debug, Clone, Default)]
+pub struct PageInfo {
+    /// Number of the page, starting at 1.
+    pub number: usize,
+    /// Number of pages in the document.
+    pub count: usize,
+    marks: BTreeMap<String, String>,
+}
+
+impl PageInfo {
+    pub fn new(number: usize, count: usize, marks: BTreeMap<String, String>) -> Self {
+        Self {
+            number,
+            count,
+            marks,
+        }
+    }
+
+    /// The first value of the mark on this page, or the last one before it if it is not set on this page.
+    pub fn mark(&self, name: &str) -> Option<&str> {
+        self.marks.get(name).map(String::as_str)
+    }
+}
+
+/// Builds the content of a header or footer for one page.
+pub type RegionBuilder<'a> = Box<dyn Fn(&PageInfo) -> Vec<Node> + 'a>;
+
+pub struct PageRegion<'a> {
+    pub builder: RegionBuilder<'a>,
+    /// Space reserved for the region, if `None` it is measured with the content built for the first page.
+    pub height: Option<Pt>,
+    /// Space between the region and the body of the page.
+    pub spacing: Pt,
+}
+
+impl<'a> PageRegion<'a> {
+    pub fn new<F: Fn(&PageInfo) -> Vec<Node> + 'a>(
+        builder: F,
+        height: Option<Pt>,
+        spacing: Pt,
+    ) -> Self {
+        Self {
+            builder: Box::new(builder),
+            height,
+            spacing,
+        }
+    }
+}
+
+/// Describes the parts of every page around the content.
+pub struct PageTemplate<'a> {
+    pub margin: PaddingValues<Pt>,
+    pub header: Option<PageRegion<'a>>,
+    pub footer: Option<PageRegion<'a>>,
+}
+
+impl<'a> PageTemplate<'a> {
+    pub fn new(margin: PaddingValues<Pt>) -> Self {
+        Self {
+            margin,
+            header: None,
+            footer: None,
+        }
+    }
+}
+
+impl Default for PageTemplate<'_> {
+    fn default() -> Self {
+        Self::new(PaddingValues::all(Pt(0.0)))
+    }
+}
@@ -0,0 +1,53 @@
+use acryl_core::math::Pt;
+
+use super::{
+    item::{Item, ItemSums},
+    Line,
+};
+
+/// First-fit line breaking: every line takes as many items as fit, breaking at the last possible point.
+///
+/// Always succeeds, but boxes wider than `line_width` overflow their line.
+pub(super) fn break_lines<T>(items: &[Item<T>], line_width: Pt) -> Vec<Line> {
+    let sums = ItemSums::new(items);
+
+    let mut lines = Vec::new();
+    let mut start = 0;
+    let mut last_fitting: Option<usize> = None;
+
+    let mut position = 0;
+
+    while position < items.len() {
+        if !Item::is_breakpoint(items, position) {
+            position += 1;
+            continue;
+        }
+
+        let ratio = sums.adjustment_ratio(items, start, position, line_width);
+
+        let end = match (ratio < -1.0, last_fitting) {
+            // Overfull, break at the last point that still fit
+            (true, Some(end)) => end,
+            // Not even the first breakpoint fits, so the line has to overflow
+            (true, None) => position,
+            _ if items[position].is_forced_break() => position,
+            _ => {
+                last_fitting = Some(position);
+                position += 1;
+                continue;
+            }
+        };
+
+        lines.push(Line {
+            start,
+            end,
+            ratio: sums.adjustment_ratio(items, start, end, line_width),
+        });
+
+        start = Item::line_start(items, end);
+        last_fitting = None;
+        position = end + 1;
+    }
+
+    lines
+}
@@ -0,0 +1,143 @@
+use acryl_core::math::Pt;
+
+/// Penalties at or above this value never break, at or below its negation always do.
+pub const INFINITE_PENALTY: f64 = 10_000.0;
+
+/// Stretch of the glue that fills the last line of a paragraph.
+pub const INFINITE_STRETCH: Pt = Pt(100_000.0);
+
+/// The box/glue/penalty model used by the line breakers.
+#[derive(Debug)]
+pub enum Item<T> {
+    /// Content that can not be broken or resized.
+    Box { width: Pt, content: T },
+    /// Space between boxes, which may be a line break if it follows a box.
+    Glue { width: Pt, stretch: Pt, shrink: Pt },
+    /// A possible line break with a cost. `width` is only added when breaking here.
+    Penalty {
+        width: Pt,
+        penalty: f64,
+        flagged: bool,
+    },
+}
+
+impl<T> Item<T> {
+    pub fn width(&self) -> Pt {
+        match self {
+            Item::Box { width, .. } => *width,
+            Item::Glue { width, .. } => *width,
+            Item::Penalty { .. } => Pt(0.0),
+        }
+    }
+
+    pub fn is_box(&self) -> bool {
+        matches!(self, Item::Box { .. })
+    }
+
+    pub fn is_forced_break(&self) -> bool {
+        matches!(self, Item::Penalty { penalty, .. } if *penalty <= -INFINITE_PENALTY)
+    }
+
+    /// Whether the paragraph may be broken at `index`.
+    pub fn is_breakpoint(items: &[Self], index: usize) -> bool {
+        match &items[index] {
+            Item::Box { .. } => false,
+            Item::Glue { .. } => index > 0 && items[index - 1].is_box(),
+            Item::Penalty { penalty, .. } => *penalty < INFINITE_PENALTY,
+        }
+    }
+
+    /// The first item of a line that starts after a break at `index`, skipping glue and penalties.
+    pub fn line_start(items: &[Self], index: usize) -> usize {
+        let mut start = index + 1;
+
+        while start < items.len() && !items[start].is_box() && !items[start].is_forced_break() {
+            start += 1;
+        }
+
+        start
+    }
+}
+
+/// Running totals over the items, so that the natural width, stretch and shrink of any range can be looked up.
+pub struct ItemSums {
+    width: Vec<Pt>,
+    stretch: Vec<Pt>,
+    shrink: Vec<Pt>,
+}
+
+impl ItemSums {
+    pub fn new<T>(items: &[Item<T>]) -> Self {
+        let mut sums = Self {
+            width: Vec::with_capacity(items.len() + 1),
+            stretch: Vec::with_capacity(items.len() + 1),
+            shrink: Vec::with_capacity(items.len() + 1),
+        };
+
+        let (mut width, mut stretch, mut shrink) = (Pt(0.0), Pt(0.0), Pt(0.0));
+
+        for item in items {
+            sums.width.push(width);
+            sums.stretch.push(stretch);
+            sums.shrink.push(shrink);
+
+            width += item.width();
+
+            if let Item::Glue {
+                stretch: glue_stretch,
+                shrink: glue_shrink,
+                ..
+            } = item
+            {
+                stretch += *glue_stretch;
+                shrink += *glue_shrink;
+            }
+        }
+
+        sums.width.push(width);
+        sums.stretch.push(stretch);
+        sums.shrink.push(shrink);
+
+        sums
+    }
+
+    /// How much the glue of a line from `start` to the break at `end` has to be stretched (positive)
+    /// or shrunk (negative) to fill `line_width`. A ratio below -1 means the line does not fit.
+    pub fn adjustment_ratio<T>(
+        &self,
+        items: &[Item<T>],
+        start: usize,
+        end: usize,
+        line_width: Pt,
+    ) -> f64 {
+        let mut width = self.width[end] - self.width[start];
+
+        if let Item::Penalty {
+            width: penalty_width,
+            ..
+        } = &items[end]
+        {
+            width += *penalty_width;
+        }
+
+        if width < line_width {
+            let stretch = self.stretch[end] - self.stretch[start];
+
+            if stretch > Pt(0.0) {
+                ((line_width - width) / stretch).0
+            } else {
+                f64::INFINITY
+            }
+        } else if width > line_width {
+            let shrink = self.shrink[end] - self.shrink[start];
+
+            if shrink > Pt(0.0) {
+                ((line_width - width) / shrink).0
+            } else {
+                f64::NEG_INFINITY
+            }
+        } else {
+            0.0
+        }
+    }
+}
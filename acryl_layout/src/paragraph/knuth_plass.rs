@@ -0,0 +1,261 @@
+use acryl_core::math::Pt;
+
+use super::{
+    item::{Item, ItemSums, INFINITE_PENALTY},
+    Line,
+};
+
+/// Lines with a higher adjustment ratio are considered infeasible.
+const TOLERANCE: f64 = 2.0;
+const LINE_PENALTY: f64 = 10.0;
+/// Added for two consecutive lines ending in a flagged penalty, e.g. two hyphens.
+const FLAGGED_DEMERITS: f64 = 100.0;
+/// Added when a line is much tighter or looser than the one before.
+const FITNESS_DEMERITS: f64 = 100.0;
+
+struct Breakpoint {
+    position: usize,
+    line: usize,
+    fitness: usize,
+    ratio: f64,
+    demerits: f64,
+    previous: Option<usize>,
+}
+
+/// Total-fit line breaking as described by Knuth and Plass in "Breaking Paragraphs into Lines".
+///
+/// Chooses the breaks minimizing the demerits of the whole paragraph.
+/// Returns `None` if there is no way to break the paragraph without exceeding the tolerance.
+pub(super) fn break_lines<T>(items: &[Item<T>], line_width: Pt) -> Option<Vec<Line>> {
+    let sums = ItemSums::new(items);
+
+    // All breakpoints ever found, `active` indexes the ones that can still start a line
+    let mut breakpoints = vec![Breakpoint {
+        position: 0,
+        line: 0,
+        fitness: 1,
+        ratio: 0.0,
+        demerits: 0.0,
+        previous: None,
+    }];
+    let mut active = vec![0];
+
+    for position in 0..items.len() {
+        if !Item::is_breakpoint(items, position) {
+            continue;
+        }
+
+        let (penalty, flagged) = match &items[position] {
+            Item::Penalty {
+                penalty, flagged, ..
+            } => (*penalty, *flagged),
+            _ => (0.0, false),
+        };
+
+        // The best candidate per fitness class
+        let mut candidates: [Option<Breakpoint>; 4] = Default::default();
+
+        active.retain(|&index| {
+            let breakpoint = &breakpoints[index];
+            let start = line_start(items, breakpoint);
+
+            let ratio = sums.adjustment_ratio(items, start, position, line_width);
+
+            if (-1.0..=TOLERANCE).contains(&ratio) {
+                let mut demerits = line_demerits(ratio, penalty);
+
+                if flagged && is_flagged(items, breakpoint.position) {
+                    demerits += FLAGGED_DEMERITS;
+                }
+
+                let fitness = fitness_class(ratio);
+
+                if fitness.abs_diff(breakpoint.fitness) > 1 {
+                    demerits += FITNESS_DEMERITS;
+                }
+
+                demerits += breakpoint.demerits;
+
+                let candidate = &mut candidates[fitness];
+
+                if candidate
+                    .as_ref()
+                    .is_none_or(|candidate| demerits < candidate.demerits)
+                {
+                    *candidate = Some(Breakpoint {
+                        position,
+                        line: breakpoint.line + 1,
+                        fitness,
+                        ratio,
+                        demerits,
+                        previous: Some(index),
+                    });
+                }
+            }
+
+            // Lines from an overfull breakpoint only get longer, and nothing continues past a forced break
+            ratio >= -1.0 && !items[position].is_forced_break()
+        });
+
+        for candidate in candidates.into_iter().flatten() {
+            active.push(breakpoints.len());
+            breakpoints.push(candidate);
+        }
+
+        if active.is_empty() {
+            return None;
+        }
+    }
+
+    let best = active
+        .into_iter()
+        .filter(|index| breakpoints[*index].position == items.len() - 1)
+        .min_by(|a, b| {
+            breakpoints[*a]
+                .demerits
+                .total_cmp(&breakpoints[*b].demerits)
+        })?;
+
+    let mut lines = Vec::new();
+    let mut current = best;
+
+    while let Some(previous) = breakpoints[current].previous {
+        let breakpoint = &breakpoints[current];
+
+        lines.push(Line {
+            start: line_start(items, &breakpoints[previous]),
+            end: breakpoint.position,
+            ratio: breakpoint.ratio,
+        });
+
+        current = previous;
+    }
+
+    lines.reverse();
+
+    Some(lines)
+}
+
+/// The demerits of a line with the adjustment `ratio` that ends at a break with `penalty`, without
+/// the demerits for the lines around it.
+fn line_demerits(ratio: f64, penalty: f64) -> f64 {
+    let badness = 100.0 * ratio.abs().powi(3);
+
+    if penalty >= 0.0 {
+        (LINE_PENALTY + badness + penalty).powi(2)
+    } else if penalty > -INFINITE_PENALTY {
+        (LINE_PENALTY + badness).powi(2) - penalty.powi(2)
+    } else {
+        (LINE_PENALTY + badness).powi(2)
+    }
+}
+
+fn line_start<T>(items: &[Item<T>], breakpoint: &Breakpoint) -> usize {
+    if breakpoint.previous.is_none() {
+        0
+    } else {
+        Item::line_start(items, breakpoint.position)
+    }
+}
+
+fn is_flagged<T>(items: &[Item<T>], position: usize) -> bool {
+    matches!(
+        items.get(position),
+        Some(Item::Penalty { flagged: true, .. })
+    )
+}
+
+fn fitness_class(ratio: f64) -> usize {
+    if ratio < -0.5 {
+        0
+    } else if ratio <= 0.5 {
+        1
+    } else if ratio <= 1.0 {
+        2
+    } else {
+        3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acryl_core::math::Pt;
+
+    use super::{break_lines, line_demerits, Line};
+    use crate::paragraph::item::{Item, INFINITE_PENALTY, INFINITE_STRETCH};
+
+    /// Four words of width 10, separated by spaces of width 5 that stretch by 3 and shrink by 2.
+    fn words() -> Vec<Item<()>> {
+        let word = || Item::Box {
+            width: Pt(10.0),
+            content: (),
+        };
+        let space = || Item::Glue {
+            width: Pt(5.0),
+            stretch: Pt(3.0),
+            shrink: Pt(2.0),
+        };
+
+        vec![
+            word(),
+            space(),
+            word(),
+            space(),
+            word(),
+            space(),
+            word(),
+            Item::Glue {
+                width: Pt(0.0),
+                stretch: INFINITE_STRETCH,
+                shrink: Pt(0.0),
+            },
+            Item::Penalty {
+                width: Pt(0.0),
+                penalty: -INFINITE_PENALTY,
+                flagged: false,
+            },
+        ]
+    }
+
+    fn breaks(lines: &[Line]) -> Vec<(usize, usize)> {
+        lines.iter().map(|line| (line.start, line.end)).collect()
+    }
+
+    #[test]
+    fn lines_of_natural_width_are_not_adjusted() {
+        let items = words();
+        let lines = break_lines(&items, Pt(25.0)).unwrap();
+
+        assert_eq!(breaks(&lines), [(0, 3), (4, 8)]);
+        assert_eq!(lines[0].ratio, 0.0);
+        assert!(lines[1].ratio.abs() < 1e-3);
+    }
+
+    #[test]
+    fn ratio_is_the_share_of_the_stretch_that_fills_the_line() {
+        let items = words();
+        let lines = break_lines(&items, Pt(27.0)).unwrap();
+
+        assert_eq!(breaks(&lines), [(0, 3), (4, 8)]);
+        assert!((lines[0].ratio - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn lines_that_can_not_stretch_enough_are_infeasible() {
+        // Every word would be alone on a line without any glue to fill it
+        assert!(break_lines(&words(), Pt(12.0)).is_none());
+    }
+
+    #[test]
+    fn demerits_grow_with_badness_and_penalty() {
+        assert_eq!(line_demerits(0.0, 0.0), 100.0);
+        assert_eq!(line_demerits(1.0, 50.0), 160.0 * 160.0);
+        assert_eq!(line_demerits(-1.0, 0.0), 110.0 * 110.0);
+    }
+
+    #[test]
+    fn negative_penalties_reduce_demerits_unless_forced() {
+        assert_eq!(line_demerits(0.0, -50.0), 100.0 - 2500.0);
+        assert_eq!(line_demerits(0.0, -INFINITE_PENALTY), 100.0);
+    }
+}
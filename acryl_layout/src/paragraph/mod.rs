@@ -0,0 +1,86 @@
+mod greedy;
+mod item;
+mod knuth_plass;
+
+use acryl_core::{math::Pt, Color, TextAlign};
+use acryl_pdf::font::FontRef;
+
+use crate::hyphenation::Hyphenation;
+
+pub(crate) use item::{Item, INFINITE_PENALTY, INFINITE_STRETCH};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum LineBreaking {
+    /// Knuth-Plass total-fit breaking, falling back to [`LineBreaking::Greedy`] if no good breaks exist.
+    #[default]
+    Optimal,
+    Greedy,
+}
+
+/// The style of a run of text, which can change within a paragraph.
+#[derive(Clone, PartialEq)]
+pub struct TextStyle {
+    pub font: FontRef,
+    pub font_size: f64,
+    /// The color of the text, which is black if this is `None`.
+    pub color: Option<Color>,
+}
+
+impl TextStyle {
+    pub fn new(font: FontRef, font_size: f64) -> Self {
+        Self {
+            font,
+            font_size,
+            color: None,
+        }
+    }
+}
+
+/// Words of the same style, the style of a paragraph can change from one run to the next.
+pub struct TextRun {
+    pub style: TextStyle,
+    pub words: Vec<String>,
+    /// Whether the first word continues the last word of the run before, because there is no
+    /// space in between.
+    pub attached: bool,
+}
+
+#[derive(Clone)]
+pub struct ParagraphStyle {
+    /// The style of text that is added to the paragraph.
+    pub text: TextStyle,
+    pub line_breaking: LineBreaking,
+    pub align: TextAlign,
+    /// Words are only hyphenated at soft hyphens if this is `None`.
+    pub hyphenation: Option<Hyphenation>,
+}
+
+impl ParagraphStyle {
+    pub fn new(font: FontRef, font_size: f64) -> Self {
+        Self {
+            text: TextStyle::new(font, font_size),
+            line_breaking: LineBreaking::default(),
+            align: TextAlign::default(),
+            hyphenation: None,
+        }
+    }
+}
+
+/// A line of a broken paragraph.
+#[derive(Debug)]
+pub(crate) struct Line {
+    /// Index of the first item on the line.
+    pub start: usize,
+    /// Index of the item the line was broken at, which is not part of the line itself.
+    pub end: usize,
+    /// How much the glue on this line has to be stretched or shrunk, see [`item::ItemSums::adjustment_ratio`].
+    pub ratio: f64,
+}
+
+pub(crate) fn break_lines<T>(items: &[Item<T>], line_width: Pt, mode: LineBreaking) -> Vec<Line> {
+    match mode {
+        LineBreaking::Optimal => knuth_plass::break_lines(items, line_width)
+            .unwrap_or_else(|| greedy::break_lines(items, line_width)),
+        LineBreaking::Greedy => greedy::break_lines(items, line_width),
+    }
+}
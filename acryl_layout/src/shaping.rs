@@ -0,0 +1,122 @@
+use std::collections::BTreeSet;
+
+use acryl_core::math::{Pt, Vector2};
+use acryl_pdf::{font::Font, stream::PositionedGlyph};
+use rustybuzz::{shape, Face, UnicodeBuffer};
+
+/// The glyph fonts show for chars they do not have.
+const NOTDEF: u16 = 0;
+
+pub struct ShapedText {
+    pub glyphs: Vec<PositionedGlyph>,
+    pub width: Pt,
+}
+
+/// Shapes text for one font and size.
+///
+/// Parsing the font for the shaper is not free, so a `Shaper` should be reused for all runs of the same font.
+pub struct Shaper<'a> {
+    font: &'a Font,
+    /// `None` if the shaper can not read the font, then every char becomes its nominal glyph.
+    face: Option<Face<'a>>,
+    font_size: f64,
+}
+
+impl<'a> Shaper<'a> {
+    pub fn new(font: &'a Font, font_size: f64) -> Self {
+        Self {
+            font,
+            face: Face::from_slice(font.data(), 0),
+            font_size,
+        }
+    }
+
+    pub fn shape(&self, text: &str) -> ShapedText {
+        let Some(face) = &self.face else {
+            return self.shape_nominal(text);
+        };
+
+        let mut buffer = UnicodeBuffer::new();
+        buffer.push_str(text);
+        buffer.guess_segment_properties();
+
+        let output = shape(face, &[], buffer);
+
+        let scale = self.font_size / self.font.units_per_em() as f64;
+        let to_pt = |value: i32| Pt(value as f64 * scale);
+
+        let infos = output.glyph_infos();
+        let positions = output.glyph_positions();
+
+        // Clusters are in reverse order for right-to-left text, so a cluster ends at the next larger
+        // start and not at the cluster of the next glyph
+        let mut starts = infos
+            .iter()
+            .map(|info| info.cluster as usize)
+            .collect::<Vec<_>>();
+        starts.sort_unstable();
+        starts.dedup();
+
+        let mut mapped = BTreeSet::new();
+        let mut glyphs = Vec::with_capacity(infos.len());
+        let mut width = Pt(0.0);
+
+        for (info, position) in infos.iter().zip(positions) {
+            let id = info.glyph_id as u16;
+            let cluster_start = info.cluster as usize;
+
+            // All glyphs of a cluster share its text, only the first one maps back to it. A missing
+            // glyph maps to nothing, so it is not copied as text it does not show
+            let text = if id != NOTDEF && mapped.insert(cluster_start) {
+                let index = starts.partition_point(|start| *start <= cluster_start);
+                let cluster_end = starts.get(index).copied().unwrap_or(text.len());
+
+                &text[cluster_start..cluster_end]
+            } else {
+                ""
+            };
+
+            self.font.register_glyph(id, text);
+
+            let advance = to_pt(position.x_advance);
+            width += advance;
+
+            glyphs.push(PositionedGlyph {
+                id,
+                advance,
+                offset: Vector2::new(to_pt(position.x_offset), to_pt(position.y_offset)),
+            });
+        }
+
+        ShapedText { glyphs, width }
+    }
+
+    /// Places the glyph of every char of `text` with its advance, without any shaping.
+    fn shape_nominal(&self, text: &str) -> ShapedText {
+        let mut glyphs = Vec::new();
+        let mut width = Pt(0.0);
+
+        for ch in text.chars() {
+            let id = self.font.get_char_id(ch).unwrap_or(NOTDEF);
+            let advance = self.font.glyph_advance(id) * self.font_size;
+
+            let mut buffer = [0; 4];
+            let text = if id == NOTDEF {
+                ""
+            } else {
+                ch.encode_utf8(&mut buffer)
+            };
+
+            self.font.register_glyph(id, text);
+            width += advance;
+
+            glyphs.push(PositionedGlyph {
+                id,
+                advance,
+                offset: Vector2::ZERO,
+            });
+        }
+
+        ShapedText { glyphs, width }
+    }
+}
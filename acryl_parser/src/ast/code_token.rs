@@ -1,9 +1,23 @@
+use std::collections::BTreeMap;
+
+use acryl_core::{math::Length, Color};
+
+use crate::Spanned;
+
+use super::Expr;
+
 #[derive(Debug)]
 pub enum CodeToken<'src> {
     Ident(&'src str),
-    Str(&'src str),
+    Str(String),
     Int(i64),
     Float(f64),
+    Length(Length),
+    Color(Color),
+    /// `[a, b, c]`
+    List(Vec<Spanned<Expr<'src>>>),
+    /// `{ key: value }`
+    Dict(BTreeMap<&'src str, Spanned<Expr<'src>>>),
 }
 
 
@@ -35,4 +49,41 @@ impl<'src> CodeToken<'src> {
             _ => None
         }
     }
+
+    pub fn as_length(&self) -> Option<&Length> {
+        match self {
+            Self::Length(value) => Some(value),
+            _ => None
+        }
+    }
+
+    pub fn as_color(&self) -> Option<&Color> {
+        match self {
+            Self::Color(value) => Some(value),
+            _ => None
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Spanned<Expr<'src>>]> {
+        match self {
+            Self::List(value) => Some(value),
+            _ => None
+        }
+    }
+
+    pub fn as_dict(&self) -> Option<&BTreeMap<&'src str, Spanned<Expr<'src>>>> {
+        match self {
+            Self::Dict(value) => Some(value),
+            _ => None
+        }
+    }
+
+    /// Returns ints and floats as a float.
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Self::Int(value) => Some(*value as f64),
+            Self::Float(value) => Some(*value),
+            _ => None
+        }
+    }
 }
\ No newline at end of file
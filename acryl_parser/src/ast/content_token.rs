@@ -1,13 +1,55 @@
-use super::CodeToken;
+use crate::{Span, Spanned};
+
+use super::Expr;
 
 #[derive(Debug)]
 pub enum ContentToken<'src> {
+    /// Text without whitespace, words that follow each other directly belong together.
     Word(&'src str),
+    Space,
+    /// A single line break, which separates words like a space.
+    LineBreak,
+    /// An empty line, which ends a paragraph.
+    ParBreak,
+    /// Text that is used as is, including its whitespace.
+    Raw(&'src str),
+    /// Like [`ContentToken::Raw`], but every line becomes its own paragraph.
+    RawBlock(&'src str),
     Fn {
         name: &'src str,
         key: Option<&'src str>,
         arguments: Vec<Argument<'src>>,
         content: Vec<Self>,
+        span: Span,
+    },
+    /// `\def Name(params) { body }`
+    Def {
+        name: &'src str,
+        params: Vec<Param<'src>>,
+        body: Vec<Self>,
+        span: Span,
+    },
+    /// `\let name = value`
+    Let {
+        name: &'src str,
+        value: Spanned<Expr<'src>>,
+        span: Span,
+    },
+    /// `\(expr)`, which inserts the value of the expression as text.
+    Expr(Spanned<Expr<'src>>),
+    /// `\if(condition) { then } \else { otherwise }`, an `\else \if` is the only token of
+    /// `otherwise`.
+    If {
+        condition: Spanned<Expr<'src>>,
+        then: Vec<Self>,
+        otherwise: Vec<Self>,
+    },
+    /// `\for(key, value in list) { body }`, where `key` is optional.
+    For {
+        key: Option<&'src str>,
+        value: &'src str,
+        list: Spanned<Expr<'src>>,
+        body: Vec<Self>,
     },
 }
 
@@ -15,7 +57,14 @@ pub enum ContentToken<'src> {
 pub enum Argument<'src> {
     Named {
         name: &'src str,
-        value: CodeToken<'src>,
+        value: Spanned<Expr<'src>>,
     },
-    Unnamed(CodeToken<'src>),
+    Unnamed(Spanned<Expr<'src>>),
+}
+
+/// A parameter of a function definition, the parameter `content` receives the content of a call.
+#[derive(Debug)]
+pub struct Param<'src> {
+    pub name: &'src str,
+    pub default: Option<Spanned<Expr<'src>>>,
 }
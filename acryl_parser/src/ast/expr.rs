@@ -1,8 +1,13 @@
-use super::CodeToken;
+use crate::Spanned;
 
+use super::CodeToken;
 
+#[derive(Debug)]
 pub enum Expr<'src> {
     Token(CodeToken<'src>),
-    Unary(&'src str, Box<Self>),
-    Binary(Box<Self>, &'src str, Box<Self>),
-}
\ No newline at end of file
+    Unary(&'src str, Box<Spanned<Self>>),
+    Binary(Box<Spanned<Self>>, &'src str, Box<Spanned<Self>>),
+    Call(&'src str, Vec<Spanned<Self>>),
+    /// `value.name`, a field of a dict.
+    Field(Box<Spanned<Self>>, &'src str),
+}
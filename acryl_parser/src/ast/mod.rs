@@ -6,4 +6,6 @@ mod expr;
 pub use op::Op;
 pub use content_token::ContentToken;
 pub use content_token::Argument;
-pub use code_token::CodeToken;
\ No newline at end of file
+pub use content_token::Param;
+pub use code_token::CodeToken;
+pub use expr::Expr;
\ No newline at end of file
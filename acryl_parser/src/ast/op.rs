@@ -1,7 +1,7 @@
 pub struct Op;
 
 impl Op {
-    pub const ALLOWED: &'static str = "+-*/!=~^_<>?#";
+    pub const ALLOWED: &'static str = "+-*/!=~^_<>?&|";
 
     // Unary
     pub const NOT: &'static str = "!";
@@ -21,6 +21,9 @@ impl Op {
     pub const LESS: &'static str = "<";
     pub const LESS_EQUALS: &'static str = "<=";
 
+    pub const AND: &'static str = "&&";
+    pub const OR: &'static str = "||";
+
     // Other
     pub const SET: &'static str = "=";
     pub const ARROW: &'static str = "->";
@@ -36,4 +39,15 @@ impl Op {
         Self::LESS,
         Self::LESS_EQUALS,
     ];
+    pub const PRIORITY_AND: &'static [&'static str; 1] = &[Self::AND];
+    pub const PRIORITY_OR: &'static [&'static str; 1] = &[Self::OR];
+
+    /// The binary priority lists, from the one that binds the strongest to the weakest.
+    pub const BINARY: &'static [&'static [&'static str]] = &[
+        Self::PRIORITY_HIGH,
+        Self::PRIORITY_MID,
+        Self::PRIORITY_LOW,
+        Self::PRIORITY_AND,
+        Self::PRIORITY_OR,
+    ];
 }
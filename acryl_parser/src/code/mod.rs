@@ -1,10 +1,19 @@
-use chumsky::{primitive::any, Parser, prelude::Rich};
+use std::collections::BTreeMap;
 
-use crate::{ast::CodeToken, lexer::Token, parser};
+use acryl_core::{math::Length, Color};
+use chumsky::{primitive::just, recursive::recursive, select, IterParser, Parser, prelude::Rich};
+
+use crate::{ast::{CodeToken, Expr, Op}, lexer::{unescape, Token}, parser, Span, Spanned};
 
 // parser!('tokens, Token<'src>, CodeToken<'src>)
 pub fn code_token_parser<'src: 'tokens, 'tokens>() -> parser!('tokens, Token<'src>, CodeToken<'src>) {
-    any().try_map(|token, span| {
+    // Only value tokens reach `try_map`, which would otherwise hide errors further in the input,
+    // like an unclosed group or list
+    let value = select! {
+        token @ (Token::Word(_) | Token::Num(_) | Token::Length(_) | Token::Color(_) | Token::Str(_)) => token,
+    };
+
+    value.try_map(|token, span| {
         match token {
             Token::Word(word) => Ok(CodeToken::Ident(word)),
             Token::Num(num) => {
@@ -20,10 +29,217 @@ pub fn code_token_parser<'src: 'tokens, 'tokens>() -> parser!('tokens, Token<'sr
                     }
                 }
             },
-            Token::Str(content) => Ok(CodeToken::Str(content)),
-            Token::Op(_) => todo!(),
-            Token::Ctrl(_) => todo!(),
-            Token::Escape => todo!(),
+            Token::Length(length) => {
+                let split = length
+                    .find(|c: char| c.is_alphabetic() || c == '%')
+                    .unwrap_or(length.len());
+                let (value, unit) = length.split_at(split);
+
+                match value.parse() {
+                    Ok(value) => Length::with_unit(value, unit)
+                        .map(CodeToken::Length)
+                        .ok_or_else(|| Rich::custom(span, format!("unknown unit '{}'", unit))),
+                    Err(error) => Err(Rich::custom(span, format!("'{}' {}", length, error)))
+                }
+            },
+            Token::Color(color) => Color::rgb_from_hex_str(&color[1..])
+                .map(CodeToken::Color)
+                .ok_or_else(|| Rich::custom(span, format!("invalid color '{}'", color))),
+            Token::Str(content) => unescape(&content[1..content.len() - 1])
+                .map(CodeToken::Str)
+                .map_err(|message| Rich::custom(span, message)),
+            _ => unreachable!(),
         }
     })
-}
\ No newline at end of file
+}
+
+/// Skips whitespace, which only separates tokens in code.
+///
+/// Code parsers skip the whitespace in front of their tokens, never behind them, so that the
+/// whitespace after the code is left to the surrounding content.
+pub fn whitespace<'src: 'tokens, 'tokens>() -> parser!('tokens, Token<'src>, ()) {
+    select! { Token::Space(_) => () }.repeated()
+}
+
+/// A [`Token::Ctrl`] with the whitespace in front of it.
+pub fn ctrl<'src: 'tokens, 'tokens>(c: char) -> parser!('tokens, Token<'src>, Token<'src>) {
+    whitespace().ignore_then(just(Token::Ctrl(c)))
+}
+
+/// Parses an expression, the binary operators bind as strong as their priority in [`Op::BINARY`].
+pub fn expr_parser<'src: 'tokens, 'tokens>() -> parser!('tokens, Token<'src>, Spanned<Expr<'src>>) {
+    recursive(|expr| {
+        let literal = code_token_parser().map_with_span(|token, span| (Expr::Token(token), span));
+
+        let group = expr
+            .clone()
+            .delimited_by(just(Token::Ctrl('(')), ctrl(')'));
+
+        let call = select! { Token::Word(name) => name }
+            .then(
+                expr.clone()
+                    .separated_by(ctrl(','))
+                    .allow_trailing()
+                    .collect::<Vec<_>>()
+                    .delimited_by(just(Token::Ctrl('(')), ctrl(')')),
+            )
+            .map_with_span(|(name, arguments), span| (Expr::Call(name, arguments), span));
+
+        let list = expr
+            .clone()
+            .separated_by(ctrl(','))
+            .allow_trailing()
+            .collect::<Vec<_>>()
+            .delimited_by(just(Token::Ctrl('[')), ctrl(']'))
+            .map(CodeToken::List);
+
+        let dict = whitespace()
+            .ignore_then(select! { Token::Word(key) => key })
+            .then_ignore(ctrl(':'))
+            .then(expr)
+            .separated_by(ctrl(','))
+            .allow_trailing()
+            .collect::<BTreeMap<_, _>>()
+            .delimited_by(just(Token::Ctrl('{')), ctrl('}'))
+            .map(CodeToken::Dict);
+
+        let collection = list
+            .or(dict)
+            .map_with_span(|token, span| (Expr::Token(token), span));
+
+        let atom = whitespace().ignore_then(group.or(collection).or(call).or(literal));
+
+        // Fields follow their value directly, so that a dot after an expression stays punctuation
+        let field = just(Token::Ctrl('.'))
+            .ignore_then(select! { Token::Word(name) => name })
+            .map_with_span(|name, span: Span| (name, span));
+
+        let access = atom
+            .then(field.repeated().collect::<Vec<_>>())
+            .map(|(value, fields)| {
+                fields.into_iter().fold(value, |value, (name, span)| {
+                    let span = Span::from(value.1.start..span.end);
+                    (Expr::Field(Box::new(value), name), span)
+                })
+            });
+
+        let unary_op = select! {
+            Token::Op(op) if op == Op::NEGATIVE || op == Op::NOT => op,
+        };
+
+        let unary = whitespace()
+            .ignore_then(unary_op.map_with_span(|op, span: Span| (op, span)))
+            .repeated()
+            .collect::<Vec<_>>()
+            .then(access)
+            .map(|(ops, operand)| {
+                ops.into_iter().rev().fold(operand, |operand, (op, span)| {
+                    let span = Span::from(span.start..operand.1.end);
+                    (Expr::Unary(op, Box::new(operand)), span)
+                })
+            });
+
+        Op::BINARY
+            .iter()
+            .fold(unary.boxed(), |operand, ops| binary(operand, ops).boxed())
+    })
+}
+
+/// Parses `operand`s separated by any of `ops`, which are applied from left to right.
+fn binary<'src: 'tokens, 'tokens>(
+    operand: parser!('tokens, Token<'src>, Spanned<Expr<'src>>),
+    ops: &'static [&'static str],
+) -> parser!('tokens, Token<'src>, Spanned<Expr<'src>>) {
+    let op = whitespace().ignore_then(select! {
+        Token::Op(op) if ops.contains(&op) => op,
+    });
+
+    operand
+        .clone()
+        .then(op.then(operand).repeated().collect::<Vec<_>>())
+        .map(|(first, rest)| {
+            rest.into_iter().fold(first, |lhs, (op, rhs)| {
+                let span = Span::from(lhs.1.start..rhs.1.end);
+                (Expr::Binary(Box::new(lhs), op, Box::new(rhs)), span)
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ast::{CodeToken, Expr},
+        parse,
+        source_map::SourceMap,
+        ParsedFile,
+    };
+
+    /// Parses `source` as the value of a header field and writes it with explicit parentheses.
+    fn parse_expr(source: &str) -> Result<String, ()> {
+        let mut sources = SourceMap::new();
+        let file = sources.add("test.acryl", format!("doc {{ x: {} }}", source));
+
+        match parse(sources.get(file)) {
+            Ok(ParsedFile::Doc(doc)) => Ok(show(&doc.header().get("x").ok_or(())?.0)),
+            _ => Err(()),
+        }
+    }
+
+    fn show(expr: &Expr) -> String {
+        match expr {
+            Expr::Token(CodeToken::Ident(name)) => name.to_string(),
+            Expr::Token(CodeToken::Int(value)) => value.to_string(),
+            Expr::Token(CodeToken::Float(value)) => format!("{:?}", value),
+            Expr::Token(CodeToken::Str(value)) => format!("{:?}", value),
+            Expr::Token(CodeToken::List(items)) => format!(
+                "[{}]",
+                items.iter().map(|item| show(&item.0)).collect::<Vec<_>>().join(", ")
+            ),
+            Expr::Token(token) => format!("{:?}", token),
+            Expr::Unary(op, operand) => format!("({}{})", op, show(&operand.0)),
+            Expr::Binary(lhs, op, rhs) => format!("({} {} {})", show(&lhs.0), op, show(&rhs.0)),
+            Expr::Call(name, arguments) => format!(
+                "{}({})",
+                name,
+                arguments.iter().map(|argument| show(&argument.0)).collect::<Vec<_>>().join(", ")
+            ),
+            Expr::Field(value, name) => format!("{}.{}", show(&value.0), name),
+        }
+    }
+
+    #[test]
+    fn binary_operators_bind_by_priority() {
+        assert_eq!(parse_expr("1 + 2 * 3").unwrap(), "(1 + (2 * 3))");
+        assert_eq!(parse_expr("(1 + 2) * 3").unwrap(), "((1 + 2) * 3)");
+        assert_eq!(
+            parse_expr("a || b && c == 1 + 2").unwrap(),
+            "(a || (b && (c == (1 + 2))))"
+        );
+    }
+
+    #[test]
+    fn binary_operators_of_the_same_priority_apply_from_left_to_right() {
+        assert_eq!(parse_expr("1 - 2 - 3").unwrap(), "((1 - 2) - 3)");
+        assert_eq!(parse_expr("8 / 4 * 2").unwrap(), "((8 / 4) * 2)");
+    }
+
+    #[test]
+    fn unary_operators_bind_stronger_than_binary_ones() {
+        assert_eq!(parse_expr("2*-3").unwrap(), "(2 * (-3))");
+        assert_eq!(parse_expr("x==-1").unwrap(), "(x == (-1))");
+        assert_eq!(parse_expr("!!a").unwrap(), "(!(!a))");
+    }
+
+    #[test]
+    fn calls_fields_and_lists() {
+        assert_eq!(parse_expr("rgb(1, 2, 3)").unwrap(), "rgb(1, 2, 3)");
+        assert_eq!(parse_expr("doc.title.length").unwrap(), "doc.title.length");
+        assert_eq!(parse_expr("[1, \"a\", 2.5,]").unwrap(), "[1, \"a\", 2.5]");
+    }
+
+    #[test]
+    fn unknown_operators_are_errors() {
+        assert!(parse_expr("1 =! 2").is_err());
+        assert!(parse_expr("1 +").is_err());
+    }
+}
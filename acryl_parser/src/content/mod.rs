@@ -1,9 +1,9 @@
 use chumsky::{primitive::just, recursive::recursive, select, IterParser, Parser};
 
 use crate::{
-    ast::{Argument, ContentToken},
-    code::code_token_parser,
-    lexer::Token,
+    ast::{Argument, ContentToken, Op, Param},
+    code::{ctrl, expr_parser, whitespace},
+    lexer::{Token, CTRL},
     parser,
 };
 
@@ -17,27 +17,60 @@ fn raw_token<'src: 'tokens, 'tokens>() -> parser!('tokens, Token<'src>, &'src st
     select! {
         Token::Word(s) => s,
         Token::Num(s) => s,
+        Token::Length(s) => s,
+        Token::Color(s) => s,
         Token::Op(s) => s,
         Token::Str(s) => s,
+        Token::Escaped(s) => s,
+        Token::Symbol(s) => s,
+        // Only the braces delimit content, the other control characters are punctuation
+        Token::Ctrl(c) if c != '{' && c != '}' => ctrl_text(c),
     }
 }
 
+fn ctrl_text(c: char) -> &'static str {
+    let index = CTRL.find(c).unwrap_or_default();
+
+    &CTRL[index..index + c.len_utf8()]
+}
+
+/// Consecutive whitespace, comments in between are already left out by the lexer.
+fn space<'src: 'tokens, 'tokens>() -> parser!('tokens, Token<'src>, ContentToken<'src>) {
+    select! { Token::Space(s) => s }
+        .repeated()
+        .at_least(1)
+        .collect::<Vec<_>>()
+        .map(|spaces| {
+            match spaces.iter().map(|s| s.matches('\n').count()).sum::<usize>() {
+                0 => ContentToken::Space,
+                1 => ContentToken::LineBreak,
+                _ => ContentToken::ParBreak,
+            }
+        })
+}
+
 pub fn content_parser<'src: 'tokens, 'tokens>(
 ) -> parser!('tokens, Token<'src>, Vec<ContentToken<'src>>) {
     recursive(|token| {
         let word = raw_token().map(ContentToken::Word);
 
+        let raw = select! {
+            Token::Raw(s) => ContentToken::Raw(s),
+            Token::RawBlock(s) => ContentToken::RawBlock(s),
+        };
+
         let raw_ident = raw_ident();
 
         let key = just(Token::Ctrl('['))
+            .ignore_then(whitespace())
             .ignore_then(raw_token())
-            .then_ignore(just(Token::Ctrl(']')));
+            .then_ignore(ctrl(']'));
 
-        let argument = raw_ident
-            .clone()
-            .then_ignore(just(Token::Ctrl(':')))
+        let argument = whitespace()
+            .ignore_then(raw_ident.clone())
+            .then_ignore(ctrl(':'))
             .or_not()
-            .then(code_token_parser())
+            .then(expr_parser())
             .map(|(name, value)| {
                 if let Some(name) = name {
                     Argument::Named { name, value }
@@ -47,25 +80,196 @@ pub fn content_parser<'src: 'tokens, 'tokens>(
             });
 
         let fn_arguments = argument
-            .separated_by(just(Token::Ctrl(',')))
+            .separated_by(ctrl(','))
             .collect()
-            .delimited_by(just(Token::Ctrl('(')), just(Token::Ctrl(')')));
+            .delimited_by(just(Token::Ctrl('(')), ctrl(')'));
 
-        let fn_content = token.delimited_by(just(Token::Ctrl('{')), just(Token::Ctrl('}')));
+        // The key and arguments follow the name directly, content may be separated by whitespace
+        let def = definition(token.clone());
 
-        let r#fn = just(Token::Escape)
-            .ignore_then(raw_ident)
+        let fn_content = whitespace()
+            .ignore_then(token.delimited_by(just(Token::Ctrl('{')), just(Token::Ctrl('}'))));
+
+        let r#fn = select! { Token::Function(name) => name }
             .then(key.or_not())
             // raw_ident
             .then(fn_arguments.or_not())
-            .then(fn_content.or_not())
-            .map(|(((name, key), arguments), content)| ContentToken::Fn {
+            // A bracket directly after the function belongs to it, even if it can not be parsed
+            .then_ignore(
+                just(Token::Ctrl('('))
+                    .or(just(Token::Ctrl('[')))
+                    .not(),
+            )
+            .then(fn_content.clone().or_not())
+            .map_with_span(|(((name, key), arguments), content), span| ContentToken::Fn {
                 name,
                 key,
                 arguments: arguments.unwrap_or_default(),
                 content: content.unwrap_or_default(),
+                span,
             });
 
-        word.or(r#fn).repeated().collect()
+        let r#let = just(Token::Function("let"))
+            .ignore_then(whitespace())
+            .ignore_then(raw_ident.clone())
+            .then_ignore(whitespace().then(just(Token::Op(Op::SET))))
+            .then(expr_parser())
+            .map_with_span(|(name, value), span| ContentToken::Let { name, value, span });
+
+        let expr = just(Token::Escape)
+            .ignore_then(expr_parser().delimited_by(just(Token::Ctrl('(')), ctrl(')')))
+            .map(ContentToken::Expr);
+
+        let condition = expr_parser().delimited_by(just(Token::Ctrl('(')), ctrl(')'));
+
+        // `\else \if` continues the chain without another pair of braces
+        let r#if = recursive(|r#if| {
+            let otherwise = whitespace()
+                .ignore_then(just(Token::Function("else")))
+                .ignore_then(
+                    whitespace()
+                        .ignore_then(r#if)
+                        .map(|r#if| vec![r#if])
+                        .or(fn_content.clone()),
+                );
+
+            just(Token::Function("if"))
+                .ignore_then(condition)
+                .then(fn_content.clone())
+                .then(otherwise.or_not())
+                .map(|((condition, then), otherwise)| ContentToken::If {
+                    condition,
+                    then,
+                    otherwise: otherwise.unwrap_or_default(),
+                })
+        });
+
+        let pattern = whitespace()
+            .ignore_then(raw_ident.clone())
+            .then(ctrl(',').ignore_then(whitespace()).ignore_then(raw_ident).or_not())
+            .map(|(first, second)| match second {
+                Some(value) => (Some(first), value),
+                None => (None, first),
+            })
+            .then_ignore(whitespace().then(just(Token::Word("in"))))
+            .then(expr_parser())
+            .delimited_by(just(Token::Ctrl('(')), ctrl(')'));
+
+        let r#for = just(Token::Function("for"))
+            .ignore_then(pattern)
+            .then(fn_content.clone())
+            .map(|(((key, value), list), body)| ContentToken::For {
+                key,
+                value,
+                list,
+                body,
+            });
+
+        word.or(space())
+            .or(raw)
+            .or(def)
+            .or(r#let)
+            .or(expr)
+            .or(r#if)
+            .or(r#for)
+            .or(r#fn)
+            .repeated()
+            .collect()
     })
 }
+
+/// `\def Name(params) { body }`, where `content` parses the body.
+fn definition<'src: 'tokens, 'tokens>(
+    content: parser!('tokens, Token<'src>, Vec<ContentToken<'src>>),
+) -> parser!('tokens, Token<'src>, ContentToken<'src>) {
+    let param = whitespace()
+        .ignore_then(raw_ident())
+        .then(ctrl(':').ignore_then(expr_parser()).or_not())
+        .map(|(name, default)| Param { name, default });
+
+    let params = param
+        .separated_by(ctrl(','))
+        .allow_trailing()
+        .collect()
+        .delimited_by(just(Token::Ctrl('(')), ctrl(')'));
+
+    let body = whitespace()
+        .ignore_then(content.delimited_by(just(Token::Ctrl('{')), just(Token::Ctrl('}'))));
+
+    just(Token::Function("def"))
+        .ignore_then(whitespace())
+        .ignore_then(raw_ident())
+        .then(params.or_not())
+        .then(body)
+        .map_with_span(|((name, params), body), span| ContentToken::Def {
+            name,
+            params: params.unwrap_or_default(),
+            body,
+            span,
+        })
+}
+
+/// The definitions of a logic file, with nothing but whitespace in between.
+pub fn definitions_parser<'src: 'tokens, 'tokens>(
+) -> parser!('tokens, Token<'src>, Vec<ContentToken<'src>>) {
+    whitespace()
+        .ignore_then(definition(content_parser()))
+        .repeated()
+        .collect()
+        .then_ignore(whitespace())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ast::ContentToken, parse_content, source_map::SourceMap};
+
+    /// Parses `source` as content and writes words as they are and whitespace as `_`, `\n` and `¶`.
+    fn parse(source: &str) -> Vec<String> {
+        let mut sources = SourceMap::new();
+        let file = sources.add("test.acryl", source.to_string());
+        let tokens = parse_content(sources.get(file)).expect("content does not parse");
+
+        tokens
+            .iter()
+            .map(|token| match token {
+                ContentToken::Word(word) => word.to_string(),
+                ContentToken::Space => String::from("_"),
+                ContentToken::LineBreak => String::from("\n"),
+                ContentToken::ParBreak => String::from("¶"),
+                token => format!("{:?}", token),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn whitespace_separates_words() {
+        assert_eq!(parse("Hello  World"), ["Hello", "_", "World"]);
+        assert_eq!(parse("Hello\nWorld"), ["Hello", "\n", "World"]);
+        assert_eq!(parse("Hello \n World"), ["Hello", "\n", "World"]);
+    }
+
+    #[test]
+    fn empty_lines_end_the_paragraph() {
+        assert_eq!(
+            parse("Hello World\n\nGoodbye World"),
+            ["Hello", "_", "World", "¶", "Goodbye", "_", "World"]
+        );
+        assert_eq!(parse("Hello\n  \n\n\nWorld"), ["Hello", "¶", "World"]);
+    }
+
+    #[test]
+    fn comments_between_lines_do_not_end_the_paragraph() {
+        assert_eq!(parse("Hello\n// comment\nWorld"), ["Hello", "\n", "World"]);
+    }
+
+    #[test]
+    fn punctuation_is_attached_to_the_words() {
+        assert_eq!(parse("Hello, World!"), ["Hello", ",", "_", "World", "!"]);
+        assert_eq!(parse("(a) [b]: c;"), ["(", "a", ")", "_", "[", "b", "]", ":", "_", "c", ";"]);
+    }
+
+    #[test]
+    fn quotes_are_punctuation_in_content() {
+        assert_eq!(parse("say \"hi\""), ["say", "_", "\"", "hi", "\""]);
+    }
+}
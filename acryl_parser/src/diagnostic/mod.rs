@@ -0,0 +1,158 @@
+mod render;
+
+use std::fmt::Display;
+
+use chumsky::error::{Rich, RichPattern, RichReason};
+
+use crate::Span;
+
+pub use render::{DiagnosticFormat, Renderer};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A span in the source with an optional message that is shown next to it.
+#[derive(Clone, Debug)]
+pub struct Label {
+    pub span: Span,
+    pub message: Option<String>,
+}
+
+impl Label {
+    pub fn new(span: Span) -> Self {
+        Self {
+            span,
+            message: None,
+        }
+    }
+
+    pub fn with_message<M: Into<String>>(mut self, message: M) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+}
+
+/// A problem in a source file, which is rendered by a [`Renderer`].
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub primary: Label,
+    pub secondary: Vec<Label>,
+    pub expected: Vec<String>,
+    pub found: Option<String>,
+    pub notes: Vec<String>,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new<M: Into<String>>(severity: Severity, span: Span, message: M) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            primary: Label::new(span),
+            secondary: Vec::new(),
+            expected: Vec::new(),
+            found: None,
+            notes: Vec::new(),
+            help: None,
+        }
+    }
+
+    pub fn error<M: Into<String>>(span: Span, message: M) -> Self {
+        Self::new(Severity::Error, span, message)
+    }
+
+    pub fn warning<M: Into<String>>(span: Span, message: M) -> Self {
+        Self::new(Severity::Warning, span, message)
+    }
+
+    pub fn with_label<M: Into<String>>(mut self, message: M) -> Self {
+        self.primary.message = Some(message.into());
+        self
+    }
+
+    pub fn with_secondary<M: Into<String>>(mut self, span: Span, message: M) -> Self {
+        self.secondary.push(Label::new(span).with_message(message));
+        self
+    }
+
+    pub fn with_expected(mut self, expected: Vec<String>) -> Self {
+        self.expected = expected;
+        self
+    }
+
+    pub fn with_found<F: Into<String>>(mut self, found: F) -> Self {
+        self.found = Some(found.into());
+        self
+    }
+
+    pub fn with_note<N: Into<String>>(mut self, note: N) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_help<H: Into<String>>(mut self, help: H) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Moves all spans by `offset`.
+    pub(crate) fn shifted(mut self, offset: usize) -> Self {
+        let shift = |span: Span| Span::from(span.start + offset..span.end + offset);
+
+        self.primary.span = shift(self.primary.span);
+
+        for label in &mut self.secondary {
+            label.span = shift(label.span);
+        }
+
+        self
+    }
+
+    /// Converts an error of the lexer or parser, `kind` names what the input consists of.
+    pub(crate) fn from_rich<T: Display>(error: &Rich<'_, T, Span>, kind: &str) -> Self {
+        // The span at the end of the input may end before it starts
+        let span = Span::from(error.span().start..error.span().end.max(error.span().start));
+
+        if let RichReason::Custom(message) = error.reason() {
+            return Self::error(span, message.clone());
+        }
+
+        let found = error.found().map(|found| format!("'{}'", found));
+        let expected = error.expected().map(pattern_name).collect::<Vec<_>>();
+
+        let diagnostic = match &found {
+            Some(found) => Self::error(span, format!("unexpected {} {}", kind, found)),
+            None => Self::error(span, "unexpected end of input"),
+        };
+
+        diagnostic
+            .with_found(found.unwrap_or_else(|| String::from("end of input")))
+            .with_expected(expected)
+    }
+}
+
+fn pattern_name<T: Display>(pattern: &RichPattern<'_, T>) -> String {
+    match pattern {
+        RichPattern::Token(token) => format!("'{}'", &**token),
+        RichPattern::EndOfInput => String::from("end of input"),
+        pattern => pattern.to_string(),
+    }
+}
@@ -0,0 +1,403 @@
+use std::{fmt::Write, str::FromStr};
+
+use crate::{source_map::SourceMap, Span};
+
+use super::{Diagnostic, Label, Severity};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiagnosticFormat {
+    /// Plain text with ANSI colors.
+    Terminal,
+    Plain,
+    /// A JSON array with one object per diagnostic.
+    Json,
+}
+
+impl FromStr for DiagnosticFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "terminal" => Ok(Self::Terminal),
+            "plain" => Ok(Self::Plain),
+            "json" => Ok(Self::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Renders diagnostics, which are shown in the file their spans point into.
+pub struct Renderer<'a> {
+    format: DiagnosticFormat,
+    sources: &'a SourceMap,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(format: DiagnosticFormat, sources: &'a SourceMap) -> Self {
+        Self { format, sources }
+    }
+
+    pub fn render(&self, diagnostics: &[Diagnostic]) -> String {
+        match self.format {
+            DiagnosticFormat::Terminal => self.render_text(diagnostics, true),
+            DiagnosticFormat::Plain => self.render_text(diagnostics, false),
+            DiagnosticFormat::Json => self.render_json(diagnostics),
+        }
+    }
+
+    fn render_text(&self, diagnostics: &[Diagnostic], colored: bool) -> String {
+        let style = |code: &str, text: &str| {
+            if colored {
+                format!("\x1b[{}m{}\x1b[0m", code, text)
+            } else {
+                text.to_string()
+            }
+        };
+
+        let mut out = String::new();
+
+        for diagnostic in diagnostics {
+            let color = match diagnostic.severity {
+                Severity::Error => "1;31",
+                Severity::Warning => "1;33",
+            };
+
+            let primary = self.locate(&diagnostic.primary.span);
+            let gutter_width = diagnostic
+                .secondary
+                .iter()
+                .map(|label| self.locate(&label.span).line)
+                .fold(primary.line, usize::max)
+                .to_string()
+                .len();
+            let gutter = " ".repeat(gutter_width);
+
+            let _ = writeln!(
+                out,
+                "{}: {}",
+                style(color, &diagnostic.severity.to_string()),
+                style("1", &diagnostic.message)
+            );
+            let _ = writeln!(
+                out,
+                "{}{} {}:{}:{}",
+                gutter,
+                style("1;34", "-->"),
+                primary.name,
+                primary.line,
+                primary.column
+            );
+
+            let labels = std::iter::once((&diagnostic.primary, '^', color)).chain(
+                diagnostic
+                    .secondary
+                    .iter()
+                    .map(|label| (label, '-', "1;34")),
+            );
+
+            for (label, marker, color) in labels {
+                let location = self.locate(&label.span);
+                let line = location.line.to_string();
+
+                let underline = marker.to_string().repeat(location.width.max(1));
+                let underline = match &label.message {
+                    Some(message) => format!("{} {}", underline, message),
+                    None => underline,
+                };
+
+                let _ = writeln!(out, "{} {}", gutter, style("1;34", "|"));
+                let _ = writeln!(
+                    out,
+                    "{}{} {} {}",
+                    style("1;34", &line),
+                    " ".repeat(gutter_width - line.len()),
+                    style("1;34", "|"),
+                    location.text
+                );
+                let _ = writeln!(
+                    out,
+                    "{} {} {}{}",
+                    gutter,
+                    style("1;34", "|"),
+                    " ".repeat(location.column - 1),
+                    style(color, &underline)
+                );
+            }
+
+            let mut footer = Vec::new();
+
+            if !diagnostic.expected.is_empty() {
+                footer.push(("expected", join_expected(&diagnostic.expected)));
+            }
+            if let Some(found) = &diagnostic.found {
+                footer.push(("found", found.clone()));
+            }
+            for note in &diagnostic.notes {
+                footer.push(("note", note.clone()));
+            }
+            if let Some(help) = &diagnostic.help {
+                footer.push(("help", help.clone()));
+            }
+
+            for (title, text) in footer {
+                let _ = writeln!(
+                    out,
+                    "{} {} {}: {}",
+                    gutter,
+                    style("1;34", "="),
+                    style("1", title),
+                    text
+                );
+            }
+
+            out.push('\n');
+        }
+
+        out
+    }
+
+    fn render_json(&self, diagnostics: &[Diagnostic]) -> String {
+        let items = diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let secondary = diagnostic
+                    .secondary
+                    .iter()
+                    .map(|label| self.label_json(label))
+                    .collect::<Vec<_>>();
+
+                format!(
+                    "{{\"severity\":{},\"message\":{},\"file\":{},\"primary\":{},\"secondary\":[{}],\
+                     \"expected\":{},\"found\":{},\"notes\":{},\"help\":{}}}",
+                    json_string(&diagnostic.severity.to_string()),
+                    json_string(&diagnostic.message),
+                    json_string(self.locate(&diagnostic.primary.span).name),
+                    self.label_json(&diagnostic.primary),
+                    secondary.join(","),
+                    json_strings(&diagnostic.expected),
+                    json_option(diagnostic.found.as_deref()),
+                    json_strings(&diagnostic.notes),
+                    json_option(diagnostic.help.as_deref()),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        format!("[{}]\n", items.join(","))
+    }
+
+    fn label_json(&self, label: &Label) -> String {
+        let location = self.locate(&label.span);
+        // Offsets are shown relative to the file, like lines and columns
+        let span = match self.sources.lookup(label.span) {
+            Some(id) => self.sources.get(id).local(label.span),
+            None => label.span,
+        };
+
+        format!(
+            "{{\"file\":{},\"start\":{},\"end\":{},\"line\":{},\"column\":{},\"message\":{}}}",
+            json_string(location.name),
+            span.start,
+            span.end,
+            location.line,
+            location.column,
+            json_option(label.message.as_deref())
+        )
+    }
+
+    /// Finds the file and line a span starts in.
+    fn locate(&self, span: &Span) -> Location<'a> {
+        let Some(id) = self.sources.lookup(*span) else {
+            return Location {
+                name: "",
+                line: 1,
+                column: 1,
+                width: 0,
+                text: "",
+            };
+        };
+
+        let file = self.sources.get(id);
+        let source = file.source();
+        let span = file.local(*span);
+
+        let start = floor_char_boundary(source, span.start);
+        let end = floor_char_boundary(source, span.end.max(start));
+
+        let line_start = source[..start].rfind('\n').map_or(0, |index| index + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |index| start + index);
+
+        let before = &source[line_start..start];
+        // Spans over multiple lines are only marked up to the end of the first line
+        let inner = &source[start..end.min(line_end)];
+
+        Location {
+            name: file.name(),
+            line: source[..line_start].matches('\n').count() + 1,
+            column: before.chars().count() + 1,
+            width: inner.chars().count(),
+            text: source[line_start..line_end].trim_end_matches('\r'),
+        }
+    }
+}
+
+/// Position of a span in the source, `line` and `column` start at 1 and count chars.
+struct Location<'a> {
+    /// The name of the file.
+    name: &'a str,
+    line: usize,
+    column: usize,
+    width: usize,
+    text: &'a str,
+}
+
+fn join_expected(expected: &[String]) -> String {
+    match expected {
+        [] => String::new(),
+        [single] => single.clone(),
+        [rest @ .., last] => format!("{} or {}", rest.join(", "), last),
+    }
+}
+
+fn floor_char_boundary(source: &str, index: usize) -> usize {
+    let mut index = index.min(source.len());
+
+    while !source.is_char_boundary(index) {
+        index -= 1;
+    }
+
+    index
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+    out
+}
+
+fn json_strings(values: &[String]) -> String {
+    let values = values
+        .iter()
+        .map(|value| json_string(value))
+        .collect::<Vec<_>>();
+
+    format!("[{}]", values.join(","))
+}
+
+fn json_option(value: Option<&str>) -> String {
+    value.map_or(String::from("null"), json_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{diagnostic::Diagnostic, source_map::SourceMap, Span};
+
+    use super::{DiagnosticFormat, Renderer};
+
+    fn sources() -> SourceMap {
+        let mut sources = SourceMap::new();
+        sources.add("main.acryl", String::from("first line\nsecond line\n"));
+        sources.add("other.acryl", String::from("ünïcode text"));
+        sources
+    }
+
+    fn render(format: DiagnosticFormat, diagnostics: &[Diagnostic]) -> String {
+        Renderer::new(format, &sources()).render(diagnostics)
+    }
+
+    #[test]
+    fn plain_text_points_to_the_line_and_column() {
+        let diagnostic = Diagnostic::error(Span::from(18..22), "unknown function")
+            .with_label("not defined")
+            .with_help("define it with `\\def`");
+
+        assert_eq!(
+            render(DiagnosticFormat::Plain, &[diagnostic]),
+            "error: unknown function\n\
+             \x20--> main.acryl:2:8\n\
+             \x20 |\n\
+             2 | second line\n\
+             \x20 |        ^^^^ not defined\n\
+             \x20 = help: define it with `\\def`\n\
+             \n"
+        );
+    }
+
+    #[test]
+    fn secondary_labels_are_shown_in_their_own_line() {
+        let diagnostic = Diagnostic::warning(Span::from(11..17), "unused")
+            .with_secondary(Span::from(0..5), "first here")
+            .with_expected(vec![String::from("a"), String::from("b"), String::from("c")])
+            .with_note("some note");
+
+        assert_eq!(
+            render(DiagnosticFormat::Plain, &[diagnostic]),
+            "warning: unused\n\
+             \x20--> main.acryl:2:1\n\
+             \x20 |\n\
+             2 | second line\n\
+             \x20 | ^^^^^^\n\
+             \x20 |\n\
+             1 | first line\n\
+             \x20 | ----- first here\n\
+             \x20 = expected: a, b or c\n\
+             \x20 = note: some note\n\
+             \n"
+        );
+    }
+
+    #[test]
+    fn columns_count_chars_in_later_files() {
+        // "other.acryl" starts after the 23 bytes of "main.acryl" and one separating offset
+        let diagnostic = Diagnostic::error(Span::from(24 + 5..24 + 9), "bad");
+        let output = render(DiagnosticFormat::Plain, &[diagnostic]);
+
+        assert!(output.contains("--> other.acryl:1:4\n"), "{}", output);
+        assert!(output.contains("|    ^^^^\n"), "{}", output);
+    }
+
+    #[test]
+    fn terminal_output_is_colored() {
+        let diagnostic = Diagnostic::error(Span::from(0..5), "bad");
+        let output = render(DiagnosticFormat::Terminal, &[diagnostic]);
+
+        assert!(output.starts_with("\x1b[1;31merror\x1b[0m: \x1b[1mbad\x1b[0m\n"));
+    }
+
+    #[test]
+    fn json_has_offsets_relative_to_the_file() {
+        let diagnostic = Diagnostic::error(Span::from(24..26), "a \"quoted\"\nmessage")
+            .with_found("x");
+
+        assert_eq!(
+            render(DiagnosticFormat::Json, &[diagnostic]),
+            "[{\"severity\":\"error\",\"message\":\"a \\\"quoted\\\"\\nmessage\",\
+             \"file\":\"other.acryl\",\"primary\":{\"file\":\"other.acryl\",\"start\":0,\
+             \"end\":2,\"line\":1,\"column\":1,\"message\":null},\"secondary\":[],\
+             \"expected\":[],\"found\":\"x\",\"notes\":[],\"help\":null}]\n"
+        );
+    }
+
+    #[test]
+    fn no_diagnostics_are_an_empty_json_array() {
+        assert_eq!(render(DiagnosticFormat::Json, &[]), "[]\n");
+        assert_eq!(render(DiagnosticFormat::Plain, &[]), "");
+    }
+}
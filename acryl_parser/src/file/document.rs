@@ -1,6 +1,9 @@
 use std::collections::BTreeMap;
 
-use crate::ast::{CodeToken, ContentToken};
+use crate::{
+    ast::{ContentToken, Expr},
+    Spanned,
+};
 
 #[derive(Debug)]
 pub struct DocFile<'src> {
@@ -10,7 +13,7 @@ pub struct DocFile<'src> {
 
 #[derive(Debug)]
 pub struct DocFileHeader<'src> {
-    fields: BTreeMap<&'src str, CodeToken<'src>>,
+    fields: BTreeMap<&'src str, Spanned<Expr<'src>>>,
 }
 
 #[derive(Debug)]
@@ -20,11 +23,11 @@ pub struct DocFileContent<'src> {
 
 impl<'src> DocFile<'src> {
     pub(crate) fn new(
-        header: BTreeMap<&'src str, CodeToken<'src>>,
+        header: BTreeMap<&'src str, Spanned<Expr<'src>>>,
         tokens: Vec<ContentToken<'src>>,
     ) -> Self {
         Self {
-            header: DocFileHeader { fields: header },
+            header: DocFileHeader::new(header),
             content: DocFileContent { tokens },
         }
     }
@@ -40,9 +43,17 @@ impl<'src> DocFile<'src> {
 
 
 impl<'src> DocFileHeader<'src> {
-    pub fn get(&self, name: &str) -> Option<&CodeToken<'src>> {
+    pub(crate) fn new(fields: BTreeMap<&'src str, Spanned<Expr<'src>>>) -> Self {
+        Self { fields }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Spanned<Expr<'src>>> {
         self.fields.get(name)
     }
+
+    pub fn fields(&self) -> impl Iterator<Item = (&'src str, &Spanned<Expr<'src>>)> {
+        self.fields.iter().map(|(name, value)| (*name, value))
+    }
 }
 
 impl<'src> DocFileContent<'src> {
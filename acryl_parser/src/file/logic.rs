@@ -0,0 +1,34 @@
+use std::collections::BTreeMap;
+
+use crate::{ast::ContentToken, ast::Expr, Spanned};
+
+use super::DocFileHeader;
+
+/// A file of definitions that documents and other logic files import.
+#[derive(Debug)]
+pub struct LogicFile<'src> {
+    header: DocFileHeader<'src>,
+    definitions: Vec<ContentToken<'src>>,
+}
+
+impl<'src> LogicFile<'src> {
+    pub(crate) fn new(
+        header: BTreeMap<&'src str, Spanned<Expr<'src>>>,
+        definitions: Vec<ContentToken<'src>>,
+    ) -> Self {
+        Self {
+            header: DocFileHeader::new(header),
+            definitions,
+        }
+    }
+
+    /// The header has the same fields as the header of a document, but only `import` is used.
+    pub fn header(&self) -> &DocFileHeader<'src> {
+        &self.header
+    }
+
+    /// The `\def`s of the file, in the order they are written.
+    pub fn definitions(&self) -> &[ContentToken<'src>] {
+        &self.definitions
+    }
+}
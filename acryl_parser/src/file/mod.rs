@@ -1,3 +1,5 @@
 mod document;
+mod logic;
 pub use document::DocFile;
-pub use document::DocFileHeader;
\ No newline at end of file
+pub use document::DocFileHeader;
+pub use logic::LogicFile;
\ No newline at end of file
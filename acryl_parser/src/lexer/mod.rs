@@ -3,43 +3,621 @@ use chumsky::{
     prelude::Rich,
     primitive::{any, end, just, none_of, one_of},
     recovery::skip_then_retry_until,
+    recursive::recursive,
     text::{self},
     Parser, IterParser,
 };
+use unicode_ident::{is_xid_continue, is_xid_start};
+use unicode_segmentation::UnicodeSegmentation;
 
 mod token;
 use crate::{Span, Spanned, ast::Op};
 
 pub use self::token::Token;
 
+/// The characters that become a [`Token::Ctrl`].
+pub const CTRL: &str = "()[]{};:,.";
+
+const SOFT_HYPHEN: char = '\u{ad}';
+
 pub fn lexer<'src>(
 ) -> impl Parser<'src, &'src str, Vec<Spanned<Token<'src>>>, Err<Rich<'src, char, Span>>> {
-    let num = text::int(10)
-        .then(just('.').then(text::digits(10)).or_not())
-        .map_slice(Token::Num);
+    let number = text::int(10).then(just('.').then(text::digits(10)).or_not());
+
+    let unit = just("pt")
+        .or(just("mm"))
+        .or(just("cm"))
+        .or(just("in"))
+        .or(just("em"))
+        .or(just("%"));
 
+    // A number directly followed by a unit is a length, so only lengths end with a letter or `%`
+    let num = number.then(unit.or_not()).map_slice(|s: &str| {
+        if s.ends_with(|c: char| c.is_ascii_digit()) {
+            Token::Num(s)
+        } else {
+            Token::Length(s)
+        }
+    });
+
+    // Escapes are checked by `unescape`, which reports them with the span of the string
     let r#str = just('"')
-        .ignore_then(none_of('"').repeated())
+        .ignore_then(
+            none_of("\\\"")
+                .ignored()
+                .or(just('\\').then(any()).ignored())
+                .repeated(),
+        )
         .then_ignore(just('"'))
-        .map_slice(|s: &str| Token::Str(&s[1..s.len()-1]));
+        .map_slice(Token::Str);
+
+    // The body of raw text is not tokenized
+    let raw_block = just("```")
+        .then(any().and_is(just("```").not()).repeated())
+        .then(just("```"))
+        .map_slice(|s: &str| Token::RawBlock(&s[3..s.len()-3]));
 
+    let raw = just('`')
+        .then(none_of('`').repeated())
+        .then(just('`'))
+        .map_slice(|s: &str| Token::Raw(&s[1..s.len()-1]));
+
+    let ident = any()
+        .filter(|c: &char| is_xid_start(*c))
+        .then(any().filter(|c: &char| is_xid_continue(*c)).repeated())
+        .map_slice(|s: &str| s);
+
+    // Split into words by `split_words`
     let word = any()
-        .filter(char::is_ascii_alphabetic)
+        .filter(|c: &char| is_xid_start(*c))
+        // Soft hyphens mark where a word may be hyphenated
+        .then(
+            any()
+                .filter(|c: &char| is_xid_continue(*c) || *c == SOFT_HYPHEN)
+                .repeated(),
+        )
+        .map_slice(|s: &str| s)
+        .map_with_span(split_words);
+
+    let color = just('#')
         .then(any().filter(char::is_ascii_alphanumeric).repeated())
-        .map_slice(Token::Word);
+        .map_slice(Token::Color);
+
+    let ctrl = one_of(CTRL).map(Token::Ctrl);
 
-    let ctrl = one_of("()[]{};:,").map(Token::Ctrl);
+    // Operators of two characters are only taken as a whole, so that `2*-3` is `*` and `-`
+    let op = just("==")
+        .or(just("!="))
+        .or(just("<="))
+        .or(just(">="))
+        .or(just("&&"))
+        .or(just("||"))
+        .or(just("->"))
+        .ignored()
+        .or(one_of(Op::ALLOWED).ignored())
+        .map_slice(Token::Op);
 
-    let op = one_of(Op::ALLOWED).repeated().at_least(1).map_slice(Token::Op);
+    // Characters that would otherwise start a function or content
+    let escaped = just('\\')
+        .then(one_of("\\{}\""))
+        .map_slice(|s: &str| Token::Escaped(&s[1..]));
+
+    let function = just('\\').ignore_then(ident).map(Token::Function);
+
+    let keyword = |name: &'static str| {
+        just('\\')
+            .ignore_then(ident.filter(move |ident: &&str| *ident == name))
+            .map_with_span(|name, span| one(Token::Function(name), span))
+    };
 
     let escape = just("\\").to(Token::Escape);
 
-    let token = num.or(r#str).or(word).or(ctrl).or(op).or(escape);
+    let space = any()
+        .filter(|c: &char| c.is_whitespace())
+        .repeated()
+        .at_least(1)
+        .map_slice(Token::Space);
 
-    token
-        .map_with_span(|token, span| (token, span))
-        .padded()
-        .recover_with(skip_then_retry_until(any().ignored(), end()))
+    let block_comment = just("/*")
+        .then(any().and_is(just("*/").not()).repeated())
+        .then(just("*/"))
+        .ignored();
+
+    // Code is lexed where the parser expects an expression: in the arguments of a function, in
+    // `\(..)`, in a `\let` and in the header. Only code has strings, and comments can start anywhere
+    let code_comment = just("//")
+        .then(none_of('\n').repeated())
+        .ignored()
+        .or(block_comment)
+        .to(Vec::new());
+
+    // An unclosed string is a symbol, which the parser reports
+    let code_token = num
+        .or(r#str)
+        .or(color)
+        .or(one_of(";:,.").map(Token::Ctrl))
+        .or(op)
+        .or(none_of("()[]{}")
+            .filter(|c: &char| !c.is_whitespace())
+            .map_slice(Token::Symbol))
+        .map_with_span(one)
+        .boxed();
+
+    let code_inline = code_comment
+        .clone()
+        .or(word.clone())
+        .or(code_token.clone())
+        .boxed();
+
+    let code = recursive(|code| {
+        code_inline
+            .clone()
+            .or(group('(', ')', code.clone()))
+            .or(group('[', ']', code.clone()))
+            .or(group('{', '}', code))
+            .or(space.map_with_span(one))
+            .repeated()
+            .collect::<Vec<_>>()
+            .map(flatten)
+            .boxed()
+    });
+
+    // The header of a file, before its content
+    let header = ident
+        .filter(|ident: &&str| *ident == "doc" || *ident == "logic")
+        .map_with_span(|ident, span| one(Token::Word(ident), span))
+        .then(space.map_with_span(one).or_not())
+        .then(group('{', '}', code.clone()))
+        .map(|((keyword, space), fields)| flatten(vec![keyword, space.unwrap_or_default(), fields]))
+        .boxed();
+
+    // The key and arguments of a function follow its name directly
+    let call = function
+        .map_with_span(one)
+        .then(group('[', ']', code.clone()).or_not())
+        .then(group('(', ')', code.clone()).or_not())
+        .map(|((name, key), arguments)| {
+            flatten(vec![name, key.unwrap_or_default(), arguments.unwrap_or_default()])
+        })
+        .boxed();
+
+    let def = keyword("def")
+        .then(space.map_with_span(one).or_not())
+        .then(word.clone())
+        .then(group('(', ')', code.clone()).or_not())
+        .map(|(((def, space), name), params)| {
+            flatten(vec![def, space.unwrap_or_default(), name, params.unwrap_or_default()])
+        })
+        .boxed();
+
+    // The value of a `\let` ends with its line
+    let inline_space = any()
+        .filter(|c: &char| c.is_whitespace() && *c != '\n')
         .repeated()
+        .at_least(1)
+        .map_slice(Token::Space)
+        .map_with_span(one);
+
+    let r#let = keyword("let")
+        .then(
+            code_inline
+                .or(group('(', ')', code.clone()))
+                .or(group('[', ']', code.clone()))
+                .or(group('{', '}', code.clone()))
+                .or(inline_space)
+                .repeated()
+                .collect::<Vec<_>>()
+                .map(flatten),
+        )
+        .map(|(r#let, value)| flatten(vec![r#let, value]))
+        .boxed();
+
+    let expr = escape
+        .clone()
+        .map_with_span(one)
+        .then(group('(', ')', code))
+        .map(|(escape, expr)| flatten(vec![escape, expr]))
+        .boxed();
+
+    // In content, strings are not lexed, so that quotes are punctuation
+    let token = num
+        .or(raw_block)
+        .or(raw)
+        .or(color)
+        .or(ctrl)
+        .or(op)
+        .or(escaped)
+        .or(function)
+        .or(escape)
+        // Raw text is left out, so that it is reported if it is not closed
+        .or(none_of("`")
+            .filter(|c: &char| !c.is_whitespace())
+            .map_slice(Token::Symbol))
+        .boxed();
+
+    // A line comment includes its line break, so that it does not leave an empty line behind
+    let line_comment = just("//")
+        .then(none_of('\n').repeated())
+        .then(just('\n').or_not())
+        .ignored();
+
+    // In content, comments only start at the beginning of the file or after whitespace, so that
+    // text like `https://example.com` is not cut off
+    let comment = line_comment.or(block_comment).to(Vec::new());
+
+    let gap = space.map_with_span(one).or(comment.clone());
+
+    let content_space = space
+        .map_with_span(one)
+        .then(gap.clone().repeated().collect::<Vec<_>>())
+        .map(|(space, rest)| flatten(std::iter::once(space).chain(rest).collect()));
+
+    let item = word
+        .or(def)
+        .or(r#let)
+        .or(call)
+        .or(expr)
+        .or(token.map_with_span(one))
+        .or(content_space)
+        .recover_with(skip_then_retry_until(any().ignored(), end()))
+        .boxed();
+
+    gap.repeated()
+        .collect::<Vec<_>>()
+        .then(header.or_not())
+        .then(item.repeated().collect::<Vec<_>>())
+        .map(|((start, header), items)| {
+            flatten(start.into_iter().chain(header).chain(items).collect())
+        })
+}
+
+/// `open`, the code lexed by `code` and `close`, with the brackets as [`Token::Ctrl`].
+///
+/// Fails if the group is not closed, so that the brackets are lexed as content instead.
+fn group<'src>(
+    open: char,
+    close: char,
+    code: impl Parser<'src, &'src str, Vec<Spanned<Token<'src>>>, Err<Rich<'src, char, Span>>> + Clone,
+) -> impl Parser<'src, &'src str, Vec<Spanned<Token<'src>>>, Err<Rich<'src, char, Span>>> + Clone {
+    just(open)
+        .map_with_span(|c, span| one(Token::Ctrl(c), span))
+        .then(code)
+        .then(just(close).map_with_span(|c, span| one(Token::Ctrl(c), span)))
+        .map(|((open, code), close)| flatten(vec![open, code, close]))
+}
+
+fn one(token: Token<'_>, span: Span) -> Vec<Spanned<Token<'_>>> {
+    vec![(token, span)]
+}
+
+fn flatten<T>(parts: Vec<Vec<T>>) -> Vec<T> {
+    parts.into_iter().flatten().collect()
+}
+
+/// Splits text into words at the word boundaries of UAX #29, which separate the characters of
+/// scripts like Chinese or Japanese that are written without spaces.
+fn split_words(text: &str, span: Span) -> Vec<Spanned<Token<'_>>> {
+    text.split_word_bound_indices()
+        .map(|(offset, word)| {
+            let start = span.start + offset;
+            (Token::Word(word), Span::from(start..start + word.len()))
+        })
         .collect()
 }
+
+/// The character a string escape like `\n` stands for, without the backslash.
+fn escaped_char(c: char) -> Option<char> {
+    match c {
+        '"' => Some('"'),
+        '\\' => Some('\\'),
+        'n' => Some('\n'),
+        'r' => Some('\r'),
+        't' => Some('\t'),
+        '0' => Some('\0'),
+        _ => None,
+    }
+}
+
+/// Replaces the escapes in the content of a [`Token::Str`].
+///
+/// Fails for unknown escapes and for unicode escapes that are not a valid char.
+pub fn unescape(content: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(content.len());
+    let mut chars = content.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('u') => {
+                let rest = chars.as_str();
+                let digits = rest
+                    .strip_prefix('{')
+                    .and_then(|rest| rest.split_once('}'))
+                    .map(|(digits, _)| digits)
+                    .filter(|digits| (1..=6).contains(&digits.len()))
+                    .ok_or_else(|| "invalid unicode escape, expected '\\u{..}'".to_string())?;
+
+                let c = u32::from_str_radix(digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| format!("invalid unicode escape '\\u{{{}}}'", digits))?;
+
+                out.push(c);
+                chars = rest[digits.len() + 2..].chars();
+            }
+            Some(c) => out.push(
+                escaped_char(c).ok_or_else(|| format!("unknown escape '\\{}'", c))?,
+            ),
+            None => {}
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use chumsky::Parser;
+
+    use super::{lexer, unescape, Token};
+
+    fn lex(source: &str) -> Vec<Token<'_>> {
+        let (tokens, errors) = lexer().parse(source).into_output_errors();
+        assert!(errors.is_empty(), "errors while lexing {:?}", source);
+
+        tokens
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(token, _)| token)
+            .collect()
+    }
+
+    #[test]
+    fn operators_of_two_characters_are_one_token() {
+        assert_eq!(
+            lex("a==b!=c<=d>=e&&f||g->h"),
+            vec![
+                Token::Word("a"),
+                Token::Op("=="),
+                Token::Word("b"),
+                Token::Op("!="),
+                Token::Word("c"),
+                Token::Op("<="),
+                Token::Word("d"),
+                Token::Op(">="),
+                Token::Word("e"),
+                Token::Op("&&"),
+                Token::Word("f"),
+                Token::Op("||"),
+                Token::Word("g"),
+                Token::Op("->"),
+                Token::Word("h"),
+            ]
+        );
+    }
+
+    #[test]
+    fn operators_in_a_row_are_separate_tokens() {
+        assert_eq!(
+            lex("2*-3"),
+            vec![Token::Num("2"), Token::Op("*"), Token::Op("-"), Token::Num("3")]
+        );
+        assert_eq!(
+            lex("x==-1"),
+            vec![Token::Word("x"), Token::Op("=="), Token::Op("-"), Token::Num("1")]
+        );
+        assert_eq!(lex("!!"), vec![Token::Op("!"), Token::Op("!")]);
+    }
+
+    #[test]
+    fn numbers_with_a_unit_are_lengths() {
+        assert_eq!(
+            lex("12pt 1.5mm 2cm 1in 0.5em 50%"),
+            vec![
+                Token::Length("12pt"),
+                Token::Space(" "),
+                Token::Length("1.5mm"),
+                Token::Space(" "),
+                Token::Length("2cm"),
+                Token::Space(" "),
+                Token::Length("1in"),
+                Token::Space(" "),
+                Token::Length("0.5em"),
+                Token::Space(" "),
+                Token::Length("50%"),
+            ]
+        );
+        assert_eq!(lex("3.25"), vec![Token::Num("3.25")]);
+    }
+
+    #[test]
+    fn words_are_split_at_unicode_word_boundaries() {
+        assert_eq!(
+            lex("Brändle écrit"),
+            vec![Token::Word("Brändle"), Token::Space(" "), Token::Word("écrit")]
+        );
+        // Kanji and kana are separate words, so lines may break between them
+        assert_eq!(
+            lex("日本語のテキスト"),
+            vec![
+                Token::Word("日"),
+                Token::Word("本"),
+                Token::Word("語"),
+                Token::Word("の"),
+                Token::Word("テキスト"),
+            ]
+        );
+        // Soft hyphens stay in the word, the hyphenator uses them
+        assert_eq!(lex("Trenn\u{ad}stelle"), vec![Token::Word("Trenn\u{ad}stelle")]);
+    }
+
+    #[test]
+    fn functions_are_unicode_identifiers() {
+        assert_eq!(
+            lex("\\Überschrift{Größe}"),
+            vec![
+                Token::Function("Überschrift"),
+                Token::Ctrl('{'),
+                Token::Word("Größe"),
+                Token::Ctrl('}'),
+            ]
+        );
+        assert_eq!(lex("\\見出し"), vec![Token::Function("見出し")]);
+    }
+
+    #[test]
+    fn comments_in_content_start_after_whitespace() {
+        assert_eq!(
+            lex("// first\na /* b */ c // d\ne"),
+            vec![
+                Token::Word("a"),
+                Token::Space(" "),
+                Token::Space(" "),
+                Token::Word("c"),
+                Token::Space(" "),
+                Token::Word("e"),
+            ]
+        );
+        assert_eq!(
+            lex("https://example.com"),
+            vec![
+                Token::Word("https"),
+                Token::Ctrl(':'),
+                Token::Op("/"),
+                Token::Op("/"),
+                Token::Word("example"),
+                Token::Ctrl('.'),
+                Token::Word("com"),
+            ]
+        );
+    }
+
+    #[test]
+    fn comments_in_code_start_anywhere() {
+        assert_eq!(
+            lex("\\f(a// b\n,/* c */d)"),
+            vec![
+                Token::Function("f"),
+                Token::Ctrl('('),
+                Token::Word("a"),
+                Token::Space("\n"),
+                Token::Ctrl(','),
+                Token::Word("d"),
+                Token::Ctrl(')'),
+            ]
+        );
+    }
+
+    #[test]
+    fn strings_are_only_lexed_in_code() {
+        assert_eq!(
+            lex("\"a \\f\" \\f(\"a \\\"b\\\"\")"),
+            vec![
+                Token::Symbol("\""),
+                Token::Word("a"),
+                Token::Space(" "),
+                Token::Function("f"),
+                Token::Symbol("\""),
+                Token::Space(" "),
+                Token::Function("f"),
+                Token::Ctrl('('),
+                Token::Str("\"a \\\"b\\\"\""),
+                Token::Ctrl(')'),
+            ]
+        );
+        assert_eq!(
+            lex("\\(\"//\")"),
+            vec![Token::Escape, Token::Ctrl('('), Token::Str("\"//\""), Token::Ctrl(')')]
+        );
+    }
+
+    #[test]
+    fn let_is_code_until_the_end_of_the_line() {
+        assert_eq!(
+            lex("\\let a = \"b\" // c\n\"d\""),
+            vec![
+                Token::Function("let"),
+                Token::Space(" "),
+                Token::Word("a"),
+                Token::Space(" "),
+                Token::Op("="),
+                Token::Space(" "),
+                Token::Str("\"b\""),
+                Token::Space(" "),
+                Token::Space("\n"),
+                Token::Symbol("\""),
+                Token::Word("d"),
+                Token::Symbol("\""),
+            ]
+        );
+    }
+
+    #[test]
+    fn the_header_is_code() {
+        assert_eq!(
+            lex("doc { a: \"b\" }"),
+            vec![
+                Token::Word("doc"),
+                Token::Space(" "),
+                Token::Ctrl('{'),
+                Token::Space(" "),
+                Token::Word("a"),
+                Token::Ctrl(':'),
+                Token::Space(" "),
+                Token::Str("\"b\""),
+                Token::Space(" "),
+                Token::Ctrl('}'),
+            ]
+        );
+    }
+
+    #[test]
+    fn unclosed_groups_are_content() {
+        assert_eq!(
+            lex("\\f(\"a"),
+            vec![
+                Token::Function("f"),
+                Token::Ctrl('('),
+                Token::Symbol("\""),
+                Token::Word("a"),
+            ]
+        );
+    }
+
+    #[test]
+    fn escapes_and_raw_text() {
+        assert_eq!(
+            lex("\\{\\}\\\\\\\"`\\x`"),
+            vec![
+                Token::Escaped("{"),
+                Token::Escaped("}"),
+                Token::Escaped("\\"),
+                Token::Escaped("\""),
+                Token::Raw("\\x"),
+            ]
+        );
+        assert_eq!(lex("```a ` \\b```"), vec![Token::RawBlock("a ` \\b")]);
+        assert_eq!(lex("\\"), vec![Token::Escape]);
+    }
+
+    #[test]
+    fn unclosed_raw_text_is_an_error() {
+        let (_, errors) = lexer().parse("`a").into_output_errors();
+
+        assert!(!errors.is_empty());
+    }
+
+    #[test]
+    fn strings_are_unescaped() {
+        assert_eq!(unescape("a\\\"b\\\\c\\n\\t"), Ok("a\"b\\c\n\t".to_string()));
+        assert_eq!(unescape("\\u{e9}\\u{1F600}!"), Ok("é😀!".to_string()));
+        assert!(unescape("\\q").is_err());
+        assert!(unescape("\\u{110000}").is_err());
+        assert!(unescape("\\u{}").is_err());
+        assert!(unescape("\\u00e9").is_err());
+    }
+}
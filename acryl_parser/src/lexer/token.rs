@@ -1,11 +1,50 @@
+use std::fmt::Display;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Token<'src> {
     Word(&'src str),
     Num(&'src str),
+    /// A number followed by a unit, like `12pt` or `50%`.
+    Length(&'src str),
+    /// A `#` followed by the hex digits of a color.
+    Color(&'src str),
+    /// A string including its quotes, with its escapes still in place.
     Str(&'src str),
+    /// Text between backticks, which is not tokenized.
+    Raw(&'src str),
+    /// Text between triple backticks, which is not tokenized.
+    RawBlock(&'src str),
 
     Op(&'src str),
     Ctrl(char),
+    /// A backslash that is not followed by a function name or an escaped character.
     Escape,
-}
\ No newline at end of file
+    /// The name of a function, without its backslash.
+    Function(&'src str),
+    /// A character that is escaped with a backslash, like `\{`.
+    Escaped(&'src str),
+    /// Any other character, like punctuation in text.
+    Symbol(&'src str),
+    /// A run of whitespace, which separates words and paragraphs in content.
+    Space(&'src str),
+}
+impl<'src> Display for Token<'src> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "{}", word),
+            Token::Num(num) => write!(f, "{}", num),
+            Token::Length(length) => write!(f, "{}", length),
+            Token::Color(color) => write!(f, "{}", color),
+            Token::Str(str) => write!(f, "{}", str),
+            Token::Raw(raw) => write!(f, "`{}`", raw),
+            Token::RawBlock(raw) => write!(f, "```{}```", raw),
+            Token::Op(op) => write!(f, "{}", op),
+            Token::Ctrl(ctrl) => write!(f, "{}", ctrl),
+            Token::Escape => write!(f, "\\"),
+            Token::Function(name) => write!(f, "\\{}", name),
+            Token::Escaped(escaped) => write!(f, "\\{}", escaped),
+            Token::Symbol(symbol) => write!(f, "{}", symbol),
+            Token::Space(space) => write!(f, "{}", space.escape_debug()),
+        }
+    }
+}
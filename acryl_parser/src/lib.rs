@@ -1,26 +1,31 @@
+use std::collections::BTreeMap;
+
 pub mod ast;
 mod code;
 mod content;
+pub mod diagnostic;
 pub mod file;
 mod lexer;
-
-use std::fmt::{Debug, Display};
+pub mod source_map;
 
 use chumsky::{
     input::SpannedInput,
-    prelude::{Input, Rich},
+    prelude::Input,
     primitive::{end, just},
     select,
     span::SimpleSpan,
     IterParser, Parser,
 };
-use code::code_token_parser;
-use content::content_parser;
-use file::DocFile;
+use ast::{ContentToken, Expr};
+use code::{ctrl, expr_parser, whitespace};
+use content::{content_parser, definitions_parser};
+use diagnostic::Diagnostic;
+use file::{DocFile, LogicFile};
 use lexer::Token;
+use source_map::SourceFile;
 
-type Span = SimpleSpan<usize>;
-type Spanned<T> = (T, Span);
+pub type Span = SimpleSpan<usize>;
+pub type Spanned<T> = (T, Span);
 
 #[macro_export]
 macro_rules! parser {
@@ -37,21 +42,30 @@ macro_rules! parser {
 #[derive(Debug)]
 pub enum ParsedFile<'src> {
     Doc(DocFile<'src>),
-    Logic,
+    Logic(LogicFile<'src>),
 }
 
-fn doc_parser<'src: 'tokens, 'tokens>() -> parser!('tokens, Token<'src>, DocFile<'src>) {
-    let field = select! {Token::Word(s) => s}
-        .then_ignore(just(Token::Ctrl(':')))
-        .then(code_token_parser());
+/// The fields of a header like `doc { title: "Title" }`, after `keyword`.
+fn header_parser<'src: 'tokens, 'tokens>(
+    keyword: &'static str,
+) -> parser!('tokens, Token<'src>, BTreeMap<&'src str, Spanned<Expr<'src>>>) {
+    let field = whitespace()
+        .ignore_then(select! {Token::Word(s) => s})
+        .then_ignore(ctrl(':'))
+        .then(expr_parser());
 
     let header_fields = field
-        .separated_by(just(Token::Ctrl(',')))
+        .separated_by(ctrl(','))
         .allow_trailing()
         .collect();
 
-    let header = just(Token::Word("doc"))
-        .ignore_then(header_fields.delimited_by(just(Token::Ctrl('{')), just(Token::Ctrl('}'))));
+    whitespace()
+        .ignore_then(just(Token::Word(keyword)))
+        .ignore_then(header_fields.delimited_by(ctrl('{'), ctrl('}')))
+}
+
+fn doc_parser<'src: 'tokens, 'tokens>() -> parser!('tokens, Token<'src>, DocFile<'src>) {
+    let header = header_parser("doc");
 
     let content = content_parser();
 
@@ -60,116 +74,86 @@ fn doc_parser<'src: 'tokens, 'tokens>() -> parser!('tokens, Token<'src>, DocFile
         .map(|(header, tokens)| DocFile::new(header, tokens))
 }
 
-fn parser<'src: 'tokens, 'tokens>() -> parser!('tokens, Token<'src>, ParsedFile<'src>) {
-    doc_parser().map(ParsedFile::Doc).then_ignore(end())
+/// A logic file has no content besides its definitions, its header is optional.
+fn logic_parser<'src: 'tokens, 'tokens>() -> parser!('tokens, Token<'src>, LogicFile<'src>) {
+    header_parser("logic")
+        .or_not()
+        .then(definitions_parser())
+        .map(|(header, definitions)| LogicFile::new(header.unwrap_or_default(), definitions))
 }
 
-pub fn parse<'src>(source: &'src str) -> Option<ParsedFile<'src>> {
-    let (tokens, errors) = lexer::lexer().parse(source).into_output_errors();
+fn parser<'src: 'tokens, 'tokens>() -> parser!('tokens, Token<'src>, ParsedFile<'src>) {
+    doc_parser()
+        .map(ParsedFile::Doc)
+        .or(logic_parser().map(ParsedFile::Logic))
+        .then_ignore(end())
+}
 
-    if !errors.is_empty() {
-        println!("----- Lexer errors -----");
-        for error in errors {
-            print_error(source, &error);
-        }
-    }
+/// Parses a source file, returns the errors of the lexer or parser if there were any.
+pub fn parse(file: &SourceFile) -> Result<ParsedFile<'_>, Vec<Diagnostic>> {
+    let (tokens, mut diagnostics) = lex(file);
 
-    let tokens = tokens?;
+    let tokens = match tokens {
+        Some(tokens) => tokens,
+        None => return Err(diagnostics),
+    };
 
     let (output, errors) = parser()
-        .parse(tokens.spanned((source.len()..source.len()).into()))
+        .parse(tokens.spanned(end_span(file)))
         .into_output_errors();
 
-    if !errors.is_empty() {
-        println!("----- Parser errors -----");
-        for error in errors {
-            print_error(source, &error);
-        }
-    }
-
-    output
-}
+    diagnostics.extend(errors.iter().map(|error| Diagnostic::from_rich(error, "token")));
 
-fn print_error<'src, T: Debug>(source: &'src str, error: &Rich<'src, T>) {
-    println!("{:?}", error);
-    let slice = get_line_slice(source, error.span());
-    println!("{}", slice);
-}
-
-struct LineSlice<'a> {
-    before: &'a str,
-    inner: &'a str,
-    after: &'a str,
-    line: usize,
-    column: usize,
-}
-
-impl<'a> Display for LineSlice<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(
-            f,
-            "{}\x1b[1;30;41m{}\x1b[0m{} \x1b[1m{}:{}\x1b[0m",
-            self.before, self.inner, self.after, self.line, self.column
-        )?;
-        writeln!(f, "{}\x1b[1;30;41m↑ Here\x1b[0m", " ".repeat(self.column))
+    match output {
+        Some(output) if diagnostics.is_empty() => Ok(output),
+        _ => Err(diagnostics),
     }
 }
 
-fn get_line_slice<'src>(source: &'src str, span: &SimpleSpan) -> LineSlice<'src> {
-    let chars: Vec<_> = source.chars().collect();
+/// Parses a file that is included into a document, it has no header and consists of content only.
+pub fn parse_content(file: &SourceFile) -> Result<Vec<ContentToken<'_>>, Vec<Diagnostic>> {
+    let (tokens, mut diagnostics) = lex(file);
 
-    fn expand(start: usize, direction: isize, chars: &Vec<char>) -> usize {
-        let mut position = start;
+    let tokens = match tokens {
+        Some(tokens) => tokens,
+        None => return Err(diagnostics),
+    };
 
-        loop {
-            if position == 0 || position == (chars.len()) as usize {
-                return position;
-            }
+    let (output, errors) = content_parser()
+        .then_ignore(end())
+        .parse(tokens.spanned(end_span(file)))
+        .into_output_errors();
 
-            let next_pos = ((position as isize) + direction) as usize;
-            if let Some(c) = chars.get(next_pos) {
-                if c == &'\n' {
-                    return position;
-                }
+    diagnostics.extend(errors.iter().map(|error| Diagnostic::from_rich(error, "token")));
 
-                position = next_pos;
-            } else {
-                return position;
-            }
-        }
+    match output {
+        Some(output) if diagnostics.is_empty() => Ok(output),
+        _ => Err(diagnostics),
     }
+}
 
-    fn count(start: usize, chars: &Vec<char>) -> usize {
-        let mut position = start;
-        let mut counter = 0;
-
-        while position > 1 {
-            match chars.get(position) {
-                Some('\n') => counter += 1,
-                None => {}
-                _ => {}
-            }
-
-            position -= 1;
-        }
+/// Splits a file into tokens, their spans are moved to the offsets of the file in its [`SourceMap`].
+fn lex(file: &SourceFile) -> (Option<Vec<Spanned<Token<'_>>>>, Vec<Diagnostic>) {
+    let start = file.start();
+    let shift = |span: Span| Span::from(span.start + start..span.end + start);
 
-        counter
-    }
+    let (tokens, errors) = lexer::lexer().parse(file.source()).into_output_errors();
 
-    let inner_start = span.start;
-    let inner_end = span.end;
+    let diagnostics = errors
+        .iter()
+        .map(|error| Diagnostic::from_rich(error, "character").shifted(start))
+        .collect();
 
-    let line_start_position = expand(span.start, -1, &chars);
-    let line_end_position = expand(span.end - 1, 1, &chars) + 1;
+    let tokens = tokens.map(|tokens| {
+        tokens
+            .into_iter()
+            .map(|(token, span)| (token, shift(span)))
+            .collect()
+    });
 
-    let column = span.start - line_start_position;
-    let line = count(span.start, &chars) + 1;
+    (tokens, diagnostics)
+}
 
-    return LineSlice {
-        before: &source[line_start_position..inner_start],
-        inner: &source[inner_start..inner_end],
-        after: &source[inner_end..line_end_position],
-        line,
-        column,
-    };
+fn end_span(file: &SourceFile) -> Span {
+    (file.end()..file.end()).into()
 }
@@ -0,0 +1,137 @@
+use crate::Span;
+
+/// The source files of a document.
+///
+/// Every file gets its own range of offsets, so a [`Span`] tells which file it points into without
+/// storing the file. The ranges are separated by one offset, so the span at the end of a file does
+/// not point into the next one.
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId(usize);
+
+#[derive(Debug)]
+pub struct SourceFile {
+    name: String,
+    source: String,
+    start: usize,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file, `name` is the path it is shown with in diagnostics.
+    pub fn add<N: Into<String>>(&mut self, name: N, source: String) -> FileId {
+        let start = self.files.last().map_or(0, |file| file.end() + 1);
+
+        self.files.push(SourceFile {
+            name: name.into(),
+            source,
+            start,
+        });
+
+        FileId(self.files.len() - 1)
+    }
+
+    pub fn get(&self, id: FileId) -> &SourceFile {
+        &self.files[id.0]
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = &SourceFile> {
+        self.files.iter()
+    }
+
+    /// Finds the file a span starts in.
+    pub fn lookup(&self, span: Span) -> Option<FileId> {
+        let index = self
+            .files
+            .partition_point(|file| file.start <= span.start)
+            .checked_sub(1)?;
+
+        (span.start <= self.files[index].end()).then_some(FileId(index))
+    }
+}
+
+impl SourceFile {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The offset of the first byte of the file.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// The offset after the last byte of the file.
+    pub fn end(&self) -> usize {
+        self.start + self.source.len()
+    }
+
+    /// Converts a span of the file into a range of byte indices of its source.
+    pub fn local(&self, span: Span) -> Span {
+        Span::from(span.start - self.start..span.end.max(span.start) - self.start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Span;
+
+    use super::SourceMap;
+
+    fn sources() -> SourceMap {
+        let mut sources = SourceMap::new();
+        sources.add("a.acryl", String::from("first"));
+        sources.add("b.acryl", String::from("second"));
+        sources.add("empty.acryl", String::new());
+        sources
+    }
+
+    #[test]
+    fn files_get_ranges_after_each_other() {
+        let sources = sources();
+        let ranges = sources
+            .files()
+            .map(|file| (file.start(), file.end()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(ranges, [(0, 5), (6, 12), (13, 13)]);
+    }
+
+    #[test]
+    fn spans_are_looked_up_by_their_start() {
+        let sources = sources();
+        let name = |start: usize, end: usize| {
+            sources
+                .lookup(Span::from(start..end))
+                .map(|id| sources.get(id).name())
+        };
+
+        assert_eq!(name(0, 2), Some("a.acryl"));
+        // The end of a file still belongs to it, for errors at the end of the input
+        assert_eq!(name(5, 5), Some("a.acryl"));
+        assert_eq!(name(6, 12), Some("b.acryl"));
+        assert_eq!(name(13, 13), Some("empty.acryl"));
+        assert_eq!(name(14, 15), None);
+    }
+
+    #[test]
+    fn local_spans_index_the_source_of_the_file() {
+        let sources = sources();
+        let id = sources.lookup(Span::from(8..10)).unwrap();
+        let file = sources.get(id);
+        let span = file.local(Span::from(8..10));
+
+        assert_eq!(span, Span::from(2..4));
+        assert_eq!(&file.source()[span.into_range()], "co");
+    }
+}
@@ -37,7 +37,14 @@ impl PdfObj {
             PdfObj::UInt(value) => write!(f, "{}", value),
             PdfObj::Float(value) => write!(f, "{}", value),
             PdfObj::StringLiteral(value) => {
-                write!(f, "({})", value.replace('(', "\\(").replace(')', "\\)"))
+                write!(
+                    f,
+                    "({})",
+                    value
+                        .replace('\\', "\\\\")
+                        .replace('(', "\\(")
+                        .replace(')', "\\)")
+                )
             }
             PdfObj::HexString(value) => {
                 write!(f, "<")?;
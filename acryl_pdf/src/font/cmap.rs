@@ -7,8 +7,8 @@ use crate::data::PdfObj;
 use super::{glyph_info::GlyphInfo, Font};
 
 pub struct CMap {
-    // id -> (codepoint, width, height)
-    map: BTreeMap<u16, (char, u16, u16)>,
+    // id -> (text, width, height)
+    map: BTreeMap<u16, (String, u16, u16)>,
     max_height: u16,
     total_width: u32,
     font_units_per_em: u16,
@@ -16,28 +16,39 @@ pub struct CMap {
 
 impl From<&Font> for CMap {
     fn from(value: &Font) -> Self {
-        let mut map: BTreeMap<u16, (char, u16, u16)> = BTreeMap::new();
+        let mut map: BTreeMap<u16, (String, u16, u16)> = BTreeMap::new();
 
         let mut max_height: u16 = 0;
         let mut total_width: u32 = 0;
 
-        for (_, info) in &*value.glyph_ids() {
-            if let Some(GlyphInfo {
+        let char_glyphs = value
+            .glyph_ids()
+            .values()
+            .flatten()
+            .map(|info| (info.clone(), info.ch.to_string()))
+            .collect::<Vec<_>>();
+
+        let shaped_glyphs = value.shaped_glyphs().values().cloned().collect::<Vec<_>>();
+
+        for (info, text) in char_glyphs.into_iter().chain(shaped_glyphs) {
+            let GlyphInfo {
                 id,
-                ch,
                 advance: Vector2 { x: width, .. },
                 size: Vector2 { y: height, .. },
                 ..
-            }) = info
-            {
-                if *height > max_height {
-                    max_height = *height;
-                }
+            } = info;
 
-                total_width += *width as u32;
+            if map.contains_key(&id) {
+                continue;
+            }
 
-                map.insert(*id, (*ch, *width, *height));
+            if height > max_height {
+                max_height = height;
             }
+
+            total_width += width as u32;
+
+            map.insert(id, (text, width, height));
         }
 
         CMap {
@@ -50,21 +61,25 @@ impl From<&Font> for CMap {
 }
 
 impl CMap {
-    fn create_blocks(&self) -> Vec<Vec<(u16, char)>> {
+    fn create_blocks(&self) -> Vec<Vec<(u16, &str)>> {
         let mut current_first_byte: u8 = 0;
 
         let mut all_cmap_blocks = Vec::new();
 
         let mut current_cmap_block = Vec::new();
 
-        for (glyph_id, (unicode, _, _)) in &self.map {
+        for (glyph_id, (text, _, _)) in &self.map {
+            if text.is_empty() {
+                continue;
+            }
+
             if (*glyph_id >> 8) as u8 != current_first_byte || current_cmap_block.len() >= 100 {
                 all_cmap_blocks.push(current_cmap_block);
                 current_cmap_block = Vec::new();
                 current_first_byte = (*glyph_id >> 8) as u8;
             }
 
-            current_cmap_block.push((*glyph_id, *unicode));
+            current_cmap_block.push((*glyph_id, text.as_str()));
         }
 
         all_cmap_blocks.push(current_cmap_block);
@@ -84,8 +99,12 @@ impl CMap {
             .filter(|block| !block.is_empty() && block.len() < 100)
         {
             map.push_str(&format!("{} beginbfchar\r\n", block.len()));
-            for (glyph_id, unicode) in block {
-                map.push_str(&format!("<{:04x}> <{:04x}>\r\n", glyph_id, unicode as u16));
+            for (glyph_id, text) in block {
+                let unicode: String = text
+                    .encode_utf16()
+                    .map(|unit| format!("{:04x}", unit))
+                    .collect();
+                map.push_str(&format!("<{:04x}> <{}>\r\n", glyph_id, unicode));
             }
             map.push_str("endbfchar\r\n");
         }
@@ -8,7 +8,7 @@ use owned_ttf_parser::name_id::POST_SCRIPT_NAME;
 // use owned_ttf_parser::name_id::FULL_NAME;
 use owned_ttf_parser::FaceParsingError;
 use owned_ttf_parser::OwnedFace;
-use owned_ttf_parser::{AsFaceRef, Face};
+use owned_ttf_parser::{AsFaceRef, Face, GlyphId};
 
 use crate::data::{PdfObj, PdfObjRef};
 use crate::pdf_dict;
@@ -30,6 +30,8 @@ pub struct Font {
     name: String,
     pub(super) units_per_em: u16,
     glyph_info_cache: RefCell<BTreeMap<char, Option<GlyphInfo>>>,
+    // id -> (info, text the glyph represents)
+    shaped_glyph_cache: RefCell<BTreeMap<u16, (GlyphInfo, String)>>,
     // word_width_cache: RefCell<HashMap<String, Pt>>,
 }
 
@@ -59,6 +61,7 @@ impl Font {
             name,
             units_per_em,
             glyph_info_cache: Default::default(),
+            shaped_glyph_cache: Default::default(),
             // word_width_cache: Default::default(),
         })
     }
@@ -68,6 +71,17 @@ impl Font {
         &self.name
     }
 
+    #[inline]
+    pub fn units_per_em(&self) -> u16 {
+        self.units_per_em
+    }
+
+    /// The raw font file, e.g. for handing it to a shaper.
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        self.face.as_slice()
+    }
+
     #[inline]
     pub fn metrics(&self) -> FontMetrics {
         FontMetrics::from(self)
@@ -97,6 +111,35 @@ impl Font {
             }
         };
 
+        let info = self.create_glyph_info(glyph_id, ch);
+
+        self.glyph_info_cache.borrow_mut().insert(ch, Some(info));
+
+        self.glyph_info_cache.borrow().get(&ch).unwrap().to_owned()
+    }
+
+    /// Registers a glyph produced by a shaper, so it gets a width and a unicode mapping in the pdf.
+    ///
+    /// `text` is the part of the source text the glyph represents, which may be more than one char for ligatures.
+    /// Glyphs that were registered without text, like the later glyphs of a cluster, get the text of a
+    /// later registration.
+    pub fn register_glyph(&self, id: u16, text: &str) {
+        let mut glyphs = self.shaped_glyph_cache.borrow_mut();
+
+        if let Some((_, known)) = glyphs.get(&id) {
+            if !known.is_empty() || text.is_empty() {
+                return;
+            }
+        }
+
+        let info = self.create_glyph_info(GlyphId(id), text.chars().next().unwrap_or_default());
+
+        glyphs.insert(id, (info, text.to_owned()));
+    }
+
+    fn create_glyph_info(&self, glyph_id: GlyphId, ch: char) -> GlyphInfo {
+        let face = self.face();
+
         let bbox = face
             .glyph_bounding_box(glyph_id)
             .map_or((0, 0), |bbox| (bbox.width(), bbox.height()));
@@ -107,21 +150,22 @@ impl Font {
             face.glyph_ver_advance(glyph_id).unwrap_or(0),
         );
 
-        let id = glyph_id.0;
-        let info = GlyphInfo {
-            id,
+        GlyphInfo {
+            id: glyph_id.0,
             ch,
             advance,
             size,
             units_per_em: self.units_per_em,
-        };
-
-        self.glyph_info_cache.borrow_mut().insert(ch, Some(info));
+        }
+    }
 
-        self.glyph_info_cache.borrow().get(&ch).unwrap().to_owned()
+    /// The advance of the glyph `id` as it is written into the pdf width array, at font size 1.
+    pub fn glyph_advance(&self, id: u16) -> Pt {
+        let advance = self.face().glyph_hor_advance(GlyphId(id)).unwrap_or(0);
+        Self::unit_to_pt(self.units_per_em, advance)
     }
 
-    pub(crate) fn get_char_id(&self, c: char) -> Option<u16> {
+    pub fn get_char_id(&self, c: char) -> Option<u16> {
         self.face().glyph_index(c).map(|id| id.0)
     }
 
@@ -151,6 +195,15 @@ impl Font {
     pub(super) fn glyph_ids(&self) -> Ref<BTreeMap<char, Option<GlyphInfo>>> {
         self.glyph_info_cache.borrow()
     }
+
+    pub(super) fn shaped_glyphs(&self) -> Ref<'_, BTreeMap<u16, (GlyphInfo, String)>> {
+        self.shaped_glyph_cache.borrow()
+    }
+
+    /// Whether any text was measured or shaped with the font.
+    pub(crate) fn is_used(&self) -> bool {
+        !self.glyph_info_cache.borrow().is_empty() || !self.shaped_glyph_cache.borrow().is_empty()
+    }
 }
 
 impl<D> WritePdf<D> for &Font {
@@ -21,6 +21,13 @@ impl FontRef {
     }
 }
 
+/// References are equal if they name the same font of the resources.
+impl PartialEq for FontRef {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0 && Rc::ptr_eq(&self.1, &other.1)
+    }
+}
+
 impl AsRef<Font> for FontRef {
     fn as_ref(&self) -> &Font {
         &self.1
@@ -29,7 +29,8 @@ impl<D> WritePdf<D> for ResourceManager {
     fn write(self, writer: &mut PdfWriter<D>) -> PdfObjRef {
         let mut fields = Vec::new();
 
-        for (name, font) in &self.fonts {
+        // Fonts are loaded before it is known which ones a document uses, the others are not embedded
+        for (name, font) in self.fonts.iter().filter(|(_, font)| font.is_used()) {
             let obj_ref = font.write(writer);
             fields.push((name.to_owned().into(), obj_ref.into()));
         }
@@ -3,6 +3,7 @@ mod text_builder;
 mod path_builder;
 
 pub use stream_builder::StreamBuilder;
+pub use text_builder::PositionedGlyph;
 
 pub use path_builder::FillPaintArgs;
 pub use path_builder::StrokePaintArgs;
\ No newline at end of file
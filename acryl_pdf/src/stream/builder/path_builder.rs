@@ -66,6 +66,7 @@ impl<'builder, 'page> PathBuilder<'builder, 'page> {
     }
 }
 
+#[derive(Clone)]
 pub struct FillPaintArgs {
     pub color: Color,
     pub fill_rule: FillRule,
@@ -78,6 +79,7 @@ impl FillPaintArgs {
     }
 }
 
+#[derive(Clone)]
 pub struct StrokePaintArgs {
     pub close: bool,
     pub color: Color,
@@ -1,9 +1,12 @@
-use acryl_core::math::{Area, Pt};
+use acryl_core::{
+    math::{Area, Pt},
+    Color,
+};
 
 use crate::{
     font::FontRef,
     stream::{
-        Stream, StreamInstruction,
+        color::ColorOperation, graphics_state::GraphicsState, Stream, StreamInstruction,
     },
     structure::Page,
     util::CoordinateTransformer,
@@ -49,6 +52,20 @@ impl<'page> StreamBuilder<'page> {
     ) -> PathBuilder<'builder, 'page> {
         PathBuilder::new(self)
     }
+
+    /// Saves the graphics state, like the colors, until the matching [`StreamBuilder::restore_state`].
+    pub fn save_state(&mut self) {
+        self.push(GraphicsState::SaveState)
+    }
+
+    pub fn restore_state(&mut self) {
+        self.push(GraphicsState::RestoreState)
+    }
+
+    /// Sets the color text and filled paths are painted with.
+    pub fn set_fill_color(&mut self, color: Color) {
+        self.push(ColorOperation::FillColor(color))
+    }
 }
 
 impl<'page> StreamBuilder<'page> {
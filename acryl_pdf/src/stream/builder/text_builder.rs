@@ -1,12 +1,25 @@
 use std::rc::Rc;
 
-use acryl_core::math::{AcrylCoords, Pt, Vector2};
+use acryl_core::math::{AcrylCoords, Pt, Vector2, VectorComponent};
 
-use crate::{font::{Font, FontRef}, stream::text::{TextControl, TextStreamElement}};
+use crate::{
+    font::{Font, FontRef},
+    stream::text::{TextArrayElement, TextControl, TextStreamElement},
+};
 
 use super::StreamBuilder;
 
 
+/// A glyph placed by a shaper.
+#[derive(Debug, Clone)]
+pub struct PositionedGlyph {
+    pub id: u16,
+    /// How far the pen moves after this glyph.
+    pub advance: Pt,
+    /// Offset of the glyph from the pen position, `y` pointing up.
+    pub offset: Vector2<Pt>,
+}
+
 pub struct TextBuilder<'builder, 'page> {
     builder: &'builder mut StreamBuilder<'page>,
     font: Rc<Font>,
@@ -86,6 +99,49 @@ impl<'builder, 'page> TextBuilder<'builder, 'page> {
 
         self.builder.push(TextStreamElement::Text(bytes))
     }
+
+    /// Draws already shaped glyphs, keeping the advances and offsets chosen by the shaper.
+    pub fn draw_glyphs(&mut self, glyphs: &[PositionedGlyph]) {
+        // Offsets in a TJ array are given in thousandths of the font size
+        let scale = 1000.0 / self.font_size;
+
+        let mut rise = Pt::ZERO;
+        let mut elements = Vec::new();
+
+        for glyph in glyphs {
+            if glyph.offset.y != rise {
+                if !elements.is_empty() {
+                    self.builder
+                        .push(TextStreamElement::PositionedText(std::mem::take(&mut elements)));
+                }
+
+                rise = glyph.offset.y;
+                self.builder.push(TextStreamElement::Rise(rise));
+            }
+
+            let nominal_advance = self.font.glyph_advance(glyph.id) * self.font_size;
+
+            if glyph.offset.x != Pt::ZERO {
+                elements.push(TextArrayElement::Offset(-glyph.offset.x.0 * scale));
+            }
+
+            elements.push(TextArrayElement::Text(glyph.id.to_be_bytes().to_vec()));
+
+            let correction = nominal_advance + glyph.offset.x - glyph.advance;
+
+            if correction != Pt::ZERO {
+                elements.push(TextArrayElement::Offset(correction.0 * scale));
+            }
+        }
+
+        if !elements.is_empty() {
+            self.builder.push(TextStreamElement::PositionedText(elements));
+        }
+
+        if rise != Pt::ZERO {
+            self.builder.push(TextStreamElement::Rise(Pt::ZERO));
+        }
+    }
 }
 
 impl Drop for TextBuilder<'_, '_> {
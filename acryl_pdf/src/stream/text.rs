@@ -21,6 +21,13 @@ pub enum TextStreamElement {
     RenderMode(RenderMode),
     Rise(Pt),
     Text(Vec<u8>),
+    PositionedText(Vec<TextArrayElement>),
+}
+
+pub enum TextArrayElement {
+    Text(Vec<u8>),
+    /// Moves the next glyph to the left by the given amount in thousandths of text space units.
+    Offset(f64),
 }
 
 #[repr(u8)]
@@ -60,6 +67,17 @@ impl From<TextStreamElement> for StreamInstruction {
             TextStreamElement::RenderMode(v) => (vec![(v as u8).into()], "Tr"),
             TextStreamElement::Rise(v) => (vec![v.into()], "Ts"),
             TextStreamElement::Text(text) => (vec![PdfObj::HexString(text)], "Tj"),
+            TextStreamElement::PositionedText(elements) => {
+                let elements = elements
+                    .into_iter()
+                    .map(|element| match element {
+                        TextArrayElement::Text(text) => PdfObj::HexString(text),
+                        TextArrayElement::Offset(offset) => PdfObj::Float(offset),
+                    })
+                    .collect();
+
+                (vec![PdfObj::Array(elements)], "TJ")
+            }
         }
     }
 }
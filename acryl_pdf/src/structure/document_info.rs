@@ -7,6 +7,7 @@ pub struct DocumentInfo {
     pub title: Option<String>,
     pub author: Option<String>,
     pub subject: Option<String>,
+    pub keywords: Option<String>,
 }
 
 impl From<DocumentInfo> for PdfObj {
@@ -20,6 +21,7 @@ impl From<DocumentInfo> for PdfObj {
             "Title" => literal!(title),
             "Author" => literal!(author),
             "Subject" => literal!(subject),
+            "Keywords" => literal!(keywords),
             "Creator" => PdfObj::string_literal("Acryl"),
         )
     }
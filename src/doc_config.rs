@@ -1,66 +1,122 @@
 use acryl_core::math::{Pt, Vector2};
-use acryl_parser::file::DocFileHeader;
+use acryl_interpreter::{Header, Value};
+use acryl_layout::{hyphenation::Language, padding_values::PaddingValues};
+use acryl_parser::diagnostic::Diagnostic;
 use acryl_pdf::structure::DocumentInfo;
 
-use crate::util::page_size::PageSize;
+use crate::util::{page_builder::PageBuilder, page_size::PageSize};
 
 #[derive(Debug)]
 pub struct DocumentConfig {
     pub info: DocumentInfo,
     pub default_page_size: Vector2<Pt>,
+    pub page_margin: PaddingValues<Pt>,
+    /// Selects the hyphenation patterns, words are not hyphenated without it.
+    pub lang: Option<Language>,
+    pub hyphen_penalty: Option<f64>,
 }
 
-impl TryFrom<&DocFileHeader<'_>> for DocumentConfig {
-    type Error = &'static str;
+impl TryFrom<&Header> for DocumentConfig {
+    type Error = Diagnostic;
 
-    fn try_from(value: &DocFileHeader) -> Result<Self, Self::Error> {
-        fn some_to_result<T, R, E, F: Fn(T) -> Result<R, E>>(
-            value: Option<T>,
+    fn try_from(value: &Header) -> Result<Self, Self::Error> {
+        /// Converts the field `name` if the header has it, errors point at its value.
+        fn field<R, F: Fn(&Value) -> Result<R, &'static str>>(
+            header: &Header,
+            name: &str,
             f: F,
-        ) -> Result<Option<R>, E> {
-            match value {
-                Some(value) => f(value).map(Some),
-                None => Ok(None),
+        ) -> Result<Option<R>, Diagnostic> {
+            match (header.get(name), header.span(name)) {
+                (Some(value), Some(span)) => f(value)
+                    .map(Some)
+                    .map_err(|message| Diagnostic::error(span, message)),
+                _ => Ok(None),
             }
         }
 
-        let title = some_to_result(value.get("title"), |token| {
+        let title = field(value, "title", |token| {
             token
                 .as_str()
                 .ok_or("'title' needs to be of type str'")
                 .map(ToOwned::to_owned)
         })?;
 
-        let author = some_to_result(value.get("author"), |token| {
+        let author = field(value, "author", |token| {
             token
                 .as_str()
                 .ok_or("'author' needs to be of type str'")
                 .map(ToOwned::to_owned)
         })?;
 
-        let subject = some_to_result(value.get("subject"), |token| {
+        let subject = field(value, "subject", |token| {
             token
                 .as_str()
                 .ok_or("'subject' needs to be of type str'")
                 .map(ToOwned::to_owned)
         })?;
 
-        let page_size = match value.get("pageSize") {
-            Some(size) => size
-                .as_ident()
+        let keywords = field(value, "keywords", |token| match token {
+            Value::Str(keywords) => Ok(keywords.clone()),
+            Value::List(items) => items
+                .iter()
+                .map(|item| item.as_str())
+                .collect::<Option<Vec<_>>>()
+                .map(|keywords| keywords.join(", "))
+                .ok_or("'keywords' needs to be a list of str"),
+            _ => Err("'keywords' needs to be of type str or a list of str"),
+        })?;
+
+        let page_size: PageSize = field(value, "pageSize", |size| {
+            size.as_ident()
                 .ok_or("'pageSize' needs to be of type ident")?
                 .parse()
-                .map_err(|_| "Unknow Page Size")?,
-            None => PageSize::default(),
-        };
+                .map_err(|_| "Unknow Page Size")
+        })?
+        .unwrap_or_default();
+
+        let page_builder: PageBuilder = field(value, "pageBuilder", |builder| {
+            builder
+                .as_ident()
+                .ok_or("'pageBuilder' needs to be of type ident")?
+                .parse()
+                .map_err(|_| "Unknown Page Builder")
+        })?
+        .unwrap_or_default();
+
+        let page_margin = field(value, "margin", |margin| match margin {
+            Value::Length(length) if length.is_absolute() => Ok(PaddingValues::all(length.abs)),
+            margin => margin
+                .as_number()
+                .map(|margin| PaddingValues::all(Pt(margin)))
+                .ok_or("'margin' needs to be a number or an absolute length"),
+        })?
+        .unwrap_or_else(|| page_builder.get_margin());
+
+        let lang = field(value, "lang", |token| {
+            token
+                .as_str()
+                .ok_or("'lang' needs to be of type str")?
+                .parse()
+                .map_err(|_| "Unsupported language")
+        })?;
+
+        let hyphen_penalty = field(value, "hyphenPenalty", |token| {
+            token
+                .as_number()
+                .ok_or("'hyphenPenalty' needs to be a number")
+        })?;
 
         Ok(DocumentConfig {
             info: DocumentInfo {
                 title,
                 author,
                 subject,
+                keywords,
             },
             default_page_size: page_size.get_size(),
+            page_margin,
+            lang,
+            hyphen_penalty,
         })
     }
 }
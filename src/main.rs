@@ -1,35 +1,89 @@
 mod doc_config;
+mod modules;
 mod util;
 
 use std::{
+    collections::BTreeMap,
+    env,
     fs::{self, File},
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+    rc::Rc,
     time::Instant,
 };
 
-use acryl_core::{
-    math::Pt,
-    Color,
+use acryl_core::math::Pt;
+use acryl_interpreter::{DataFiles, DataFormat, Header, Interpreter, Label, Value};
+use acryl_layout::{
+    hyphenation::{Hyphenation, Hyphenator},
+    layout_pager::{self, LayoutPager, PagePainter},
+    page_template::{Heading, PageTemplate},
+    FONT_DEJAVU_SERIF, FONT_DEJAVU_SERIF_BOLD, FONT_DEJAVU_SERIF_ITALIC, FONT_FREE_MONO,
+    FONT_NOTO_SANS,
 };
-use acryl_layout::{layout_pager::LayoutPager, node::Node, padding_values::PaddingValues, FONT_DEJAVU_SERIF};
-use acryl_parser::{file::DocFile, parse, ParsedFile};
-use acryl_pdf::{font::Font, resource_manager::ResourceManager, stream::{FillPaintArgs, FillRule, LineCap, LineJoin, StrokePaintArgs}, structure::Document, write::PdfDocument};
-
-use rand::{rngs::StdRng, Rng, SeedableRng};
+use acryl_parser::{
+    diagnostic::{Diagnostic, DiagnosticFormat, Renderer},
+    source_map::SourceMap,
+    Span,
+};
+use acryl_pdf::{font::{Font, FontRef}, resource_manager::ResourceManager, structure::Document, write::PdfDocument};
 
-use crate::doc_config::DocumentConfig;
+use crate::{doc_config::DocumentConfig, modules::Files};
 
 const SAMPLE_FILE_PATH: &str = "examples/minimal.acryl";
 const OUT_FILE_PATH: &str = "out/minimal.pdf";
 
+/// Limits how often a document is laid out to resolve its references.
+const MAX_PASSES: usize = 5;
+
+/// The fonts documents can choose with `\set(font: name)`, besides the default font `serif`.
+const FONTS: &[(&str, &str)] = &[
+    ("serif bold", FONT_DEJAVU_SERIF_BOLD),
+    ("serif italic", FONT_DEJAVU_SERIF_ITALIC),
+    ("sans", FONT_NOTO_SANS),
+    ("mono", FONT_FREE_MONO),
+];
+
 fn main() {
     let start = Instant::now();
 
     let source = fs::read_to_string(SAMPLE_FILE_PATH).expect("could not open sample acryl file");
 
-    let doc = parse_file(&source).unwrap();
-    let file = match build_pdf_from_doc(doc) {
-        Some(file) => file,
-        None => return,
+    let args = diagnostic_format().and_then(|format| load_data().map(|data| (format, data)));
+
+    let (format, data) = match args {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            return;
+        }
+    };
+
+    let mut sources = SourceMap::new();
+    let document = sources.add(SAMPLE_FILE_PATH, source);
+
+    let loaded = Files::load(&mut sources, document, &library_path());
+    let renderer = Renderer::new(format, &sources);
+
+    let (files, header) = match loaded {
+        Ok(loaded) => loaded,
+        Err(diagnostics) => {
+            eprint!("{}", renderer.render(&diagnostics));
+            return;
+        }
+    };
+    let file = match build_pdf_from_doc(&files, &header, &sources, &data) {
+        Ok((file, warnings)) => {
+            if !warnings.is_empty() {
+                eprint!("{}", renderer.render(&warnings));
+            }
+
+            file
+        }
+        Err(diagnostics) => {
+            eprint!("{}", renderer.render(&diagnostics));
+            return;
+        }
     };
 
     let size = file
@@ -51,24 +105,104 @@ fn main() {
     );
 }
 
-fn parse_file(source: &str) -> Option<DocFile> {
-    let result = parse(&source);
+/// The format of the diagnostics is set with `--diagnostics <terminal|plain|json>`, by default they are
+/// only colored if stderr is a terminal.
+fn diagnostic_format() -> Result<DiagnosticFormat, String> {
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        if arg == "--diagnostics" {
+            let format = args.next().unwrap_or_default();
 
-    if let Some(ParsedFile::Doc(doc)) = result {
-        Some(doc)
+            return format.parse().map_err(|_| {
+                format!(
+                    "'--diagnostics' needs one of terminal|plain|json, got '{}'",
+                    format
+                )
+            });
+        }
+    }
+
+    if io::stderr().is_terminal() {
+        Ok(DiagnosticFormat::Terminal)
     } else {
-        println!("{:#?}", result);
-        println!("invalid result type");
-        None
+        Ok(DiagnosticFormat::Plain)
+    }
+}
+
+/// The directories modules are searched in, given with `--lib <dir>` and in `ACRYL_PATH`.
+fn library_path() -> Vec<PathBuf> {
+    let mut library = Vec::new();
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        if arg == "--lib" {
+            library.extend(args.next().map(PathBuf::from));
+        }
+    }
+
+    if let Some(paths) = env::var_os("ACRYL_PATH") {
+        library.extend(env::split_paths(&paths));
     }
+
+    library
+}
+
+/// Loads the fonts of [`FONTS`] that are installed, only the ones a document uses are embedded.
+fn load_fonts(
+    resource_manager: &mut ResourceManager,
+    default: &FontRef,
+) -> BTreeMap<String, FontRef> {
+    let mut fonts = BTreeMap::from([(String::from("serif"), default.clone())]);
+
+    for (name, path) in FONTS {
+        if let Ok(font) = Font::load(path) {
+            fonts.insert(name.to_string(), resource_manager.add_font(font));
+        }
+    }
+
+    fonts
+}
+
+/// The data files given with `--data key=path`, their format is chosen by their extension.
+fn load_data() -> Result<Vec<(String, Value)>, String> {
+    let mut data = Vec::new();
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        if arg != "--data" {
+            continue;
+        }
+
+        let arg = args.next().unwrap_or_default();
+        let (key, path) = arg
+            .split_once('=')
+            .ok_or_else(|| format!("'--data' needs a 'key=path', got '{}'", arg))?;
+
+        let is_name = key.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+            && key.chars().all(|c| c.is_alphanumeric() || c == '_');
+
+        if !is_name {
+            return Err(format!("'{}' of '--data' is not a valid name", key));
+        }
+
+        let format = DataFormat::of(Path::new(path)).ok_or_else(|| {
+            format!("'{}' is not a json, csv or toml file", path)
+        })?;
+
+        data.push((key.to_owned(), format.load(path)?));
+    }
+
+    Ok(data)
 }
 
-fn build_pdf_from_doc(doc: DocFile) -> Option<File> {
-    let config: DocumentConfig = doc
-        .header()
-        .try_into()
-        .map_err(|err| panic!("could not parse header '{}'", err))
-        .unwrap();
+fn build_pdf_from_doc(
+    files: &Files,
+    header: &Header,
+    sources: &SourceMap,
+    data: &[(String, Value)],
+) -> Result<(File, Vec<Diagnostic>), Vec<Diagnostic>> {
+    let config: DocumentConfig = header.try_into().map_err(|error| vec![error])?;
 
     println!("{:?}", config);
 
@@ -76,37 +210,72 @@ fn build_pdf_from_doc(doc: DocFile) -> Option<File> {
     let default_font =
         resource_manager.add_font(Font::load(FONT_DEJAVU_SERIF).expect("Font file not found"));
 
+    let fonts = load_fonts(&mut resource_manager, &default_font);
 
-    let mut page_layout = LayoutPager::new(config.default_page_size);
+    let hyphenation = config.lang.map(|lang| {
+        let mut hyphenation = Hyphenation::new(Rc::new(Hyphenator::language(lang)));
 
-    let mut rng = StdRng::from_seed([0; 32]);
+        if let Some(penalty) = config.hyphen_penalty {
+            hyphenation.penalty = penalty;
+        }
 
-    for _ in 0..20 {
-        let width = rng.gen_range(50.0..150.0);
-        let height = rng.gen_range(50.0..150.0);
-        let color = rng.gen_range(0..0xFFFFFF);
+        hyphenation
+    });
 
-        let node = Node::size(width, height);
+    let parsed = files.parse(sources);
+    let data_files = Rc::new(DataFiles::new(sources));
 
-        let node = node.with_color_box(Some(FillPaintArgs {
-            color: Color::rgb_from_hex(color),
-            fill_rule: FillRule::EvenOdd,
-        }), Some(StrokePaintArgs {
-            close: true,
-            color: Color::Gray(0),
-            line_width: Pt(2.0),
-            line_cap: LineCap::Sqare,
-            line_join: LineJoin::Bevel,
-            miter_limit: Pt(10.0),
-            dash_pattern: (Vec::new(), 0),
-        })).with_padding(PaddingValues::all(Pt(5.0)));
+    // References and the table of contents use the labels and headings of the previous pass, so the
+    // document is laid out again until they stay the same
+    let ((pages, unresolved), pass, settled) = settle(|(labels, headings)| {
+        let mut interpreter = Interpreter::new(default_font.clone())
+            .with_header(header)
+            .with_fonts(fonts.clone())
+            .with_labels(labels.clone())
+            .with_headings(headings.clone())
+            .with_data_files(data_files.clone());
 
-        page_layout.push(node);
-    }
+        for (key, value) in data {
+            interpreter = interpreter.with_value(key, value.clone());
+        }
+
+        if let Some(hyphenation) = &hyphenation {
+            interpreter = interpreter.with_hyphenation(hyphenation.clone());
+        }
+
+        let interpreter = files.prepare(&parsed, interpreter);
+
+        let evaluated = match interpreter.eval(parsed.document().content().tokens()) {
+            Ok(evaluated) => evaluated,
+            Err(errors) => {
+                return Err(errors.into_iter().map(Diagnostic::from).collect::<Vec<_>>())
+            }
+        };
 
-    let pages = page_layout.layout();
+        let template = PageTemplate {
+            margin: config.page_margin.clone(),
+            header: evaluated.header,
+            footer: evaluated.footer,
+        };
 
-    println!("created {} pages", pages.len());
+        let mut page_layout = LayoutPager::new(config.default_page_size.clone())
+            .with_template(template)
+            .with_font_size(Pt(Interpreter::DEFAULT_FONT_SIZE));
+
+        for node in evaluated.nodes {
+            page_layout.push(node);
+        }
+
+        let pages = page_layout.layout();
+        let placed = (
+            place_labels(evaluated.labels, &pages),
+            layout_pager::headings(&pages),
+        );
+
+        Ok(((pages, evaluated.unresolved), placed))
+    })?;
+
+    println!("created {} pages in {} passes", pages.len(), pass);
 
     let pages = pages.into_iter().map(|page| page.paint()).collect();
 
@@ -119,5 +288,135 @@ fn build_pdf_from_doc(doc: DocFile) -> Option<File> {
         .write(&mut out_file)
         .expect("error while writing document");
 
-    Some(out_file)
+    let mut warnings = unresolved
+        .into_iter()
+        .map(|error| Diagnostic::warning(error.span, error.message))
+        .collect::<Vec<_>>();
+
+    if !settled {
+        warnings.push(unsettled_warning(sources.get(files.document()).start()));
+    }
+
+    Ok((out_file, warnings))
+}
+
+/// The labels and headings that a pass places in the layout.
+type Placed = (BTreeMap<String, Label>, Vec<Heading>);
+
+/// Runs `pass` with the labels and headings the pass before placed, until they stay the same or
+/// there were [`MAX_PASSES`].
+///
+/// Returns the result of the last pass, the number of passes and whether they settled.
+fn settle<T, E>(
+    mut pass: impl FnMut(&Placed) -> Result<(T, Placed), E>,
+) -> Result<(T, usize, bool), E> {
+    let mut placed = Placed::default();
+    let mut count = 1;
+
+    loop {
+        let (result, next) = pass(&placed)?;
+        let settled = next == placed;
+
+        if settled || count == MAX_PASSES {
+            return Ok((result, count, settled));
+        }
+
+        placed = next;
+        count += 1;
+    }
+}
+
+/// The warning for references that did not settle, `start` is the start of the document.
+fn unsettled_warning(start: usize) -> Diagnostic {
+    // The references are spread over the document, so the warning points at its start
+    Diagnostic::warning(
+        Span::from(start..start),
+        format!(
+            "references and the table of contents did not settle after {} passes",
+            MAX_PASSES
+        ),
+    )
+    .with_note("the numbers and pages of the last pass are used, they may be wrong")
+}
+
+/// Adds the page and position of the labels from the layout.
+fn place_labels(
+    mut labels: BTreeMap<String, Label>,
+    pages: &[PagePainter],
+) -> BTreeMap<String, Label> {
+    for (index, page) in pages.iter().enumerate() {
+        for (name, position) in page.labels() {
+            if let Some(label) = labels.get_mut(name) {
+                label.page = Some(index + 1);
+                label.position = Some(position.clone());
+            }
+        }
+    }
+
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use acryl_interpreter::Label;
+    use acryl_parser::{diagnostic::Severity, Span};
+
+    use super::{settle, unsettled_warning, Placed, MAX_PASSES};
+
+    /// Places the label `a` on `page`, with the page it was on in the previous pass.
+    fn place(
+        page: impl Fn(Option<usize>) -> usize,
+    ) -> impl FnMut(&Placed) -> Result<(usize, Placed), ()> {
+        move |(labels, _)| {
+            let page = page(labels.get("a").and_then(|label| label.page));
+            let label = Label {
+                page: Some(page),
+                ..Label::default()
+            };
+
+            Ok((page, (BTreeMap::from([(String::from("a"), label)]), Vec::new())))
+        }
+    }
+
+    #[test]
+    fn passes_run_until_the_labels_stay_the_same() {
+        // The label moves a page further in every pass, until it stays on page 3
+        let result = settle(place(|page| page.map_or(1, |page| (page + 1).min(3))));
+        assert_eq!(result, Ok((3, 4, true)));
+
+        let result = settle(place(|_| 1));
+        assert_eq!(result, Ok((1, 2, true)));
+    }
+
+    #[test]
+    fn passes_stop_after_the_limit_without_settling() {
+        // The label jumps between two pages, like a reference that pushes it to the next page
+        let result = settle(place(|page| if page == Some(1) { 2 } else { 1 }));
+        let last = if MAX_PASSES % 2 == 0 { 2 } else { 1 };
+
+        assert_eq!(result, Ok((last, MAX_PASSES, false)));
+    }
+
+    #[test]
+    fn errors_end_the_passes() {
+        let mut passes = 0;
+        let result = settle(|_| -> Result<((), Placed), &str> {
+            passes += 1;
+            Err("error")
+        });
+
+        assert_eq!(result, Err("error"));
+        assert_eq!(passes, 1);
+    }
+
+    #[test]
+    fn the_warning_points_at_the_start_of_the_document() {
+        let warning = unsettled_warning(7);
+
+        assert_eq!(warning.severity, Severity::Warning);
+        assert_eq!(warning.primary.span, Span::from(7..7));
+        assert!(warning.message.contains(&MAX_PASSES.to_string()));
+    }
 }
@@ -0,0 +1,716 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use acryl_interpreter::{Arguments, Header, Interpreter, Module, Value};
+use acryl_parser::{
+    ast::{CodeToken, ContentToken, Expr},
+    diagnostic::Diagnostic,
+    file::{DocFile, LogicFile},
+    parse, parse_content,
+    source_map::{FileId, SourceMap},
+    ParsedFile, Span,
+};
+
+/// The standard library, which is used if no file of the same name is found.
+const STD: &str = include_str!("../lib/std.acryl");
+const STD_NAME: &str = "std";
+const EXTENSION: &str = "acryl";
+
+/// The files of a document: the document itself, the modules it imports and the files it includes.
+pub struct Files {
+    document: FileId,
+    /// Every module comes after the modules it imports.
+    modules: Vec<ModuleFile>,
+    /// The modules the document imports itself.
+    imports: Vec<usize>,
+    /// The included files, by the start of the span of their `\include` call.
+    includes: BTreeMap<usize, FileId>,
+}
+
+struct ModuleFile {
+    file: FileId,
+    imports: Vec<usize>,
+}
+
+/// The syntax trees of [`Files`], which borrow the sources.
+pub struct Parsed<'a> {
+    document: DocFile<'a>,
+    modules: Vec<LogicFile<'a>>,
+    includes: BTreeMap<FileId, Vec<ContentToken<'a>>>,
+}
+
+/// Identifies a file, files on disk are compared by their canonical path.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Location {
+    File(PathBuf),
+    Std,
+}
+
+/// An `\include` call with the path of the file.
+struct Include {
+    path: String,
+    span: Span,
+}
+
+struct Loader<'a> {
+    sources: &'a mut SourceMap,
+    library: &'a [PathBuf],
+    modules: Vec<ModuleFile>,
+    loaded_modules: BTreeMap<Location, usize>,
+    includes: BTreeMap<usize, FileId>,
+    loaded_includes: BTreeMap<Location, FileId>,
+    /// The files that are being loaded with their names, each one is imported or included by the
+    /// one before.
+    stack: Vec<(Location, String)>,
+}
+
+impl Files {
+    /// Loads the modules and included files of `document`, and adds them to `sources`.
+    ///
+    /// Modules are searched relative to the file that imports them first, then in the directories of
+    /// `library`. Included files are always relative to the file that includes them. A file that
+    /// imports or includes itself, directly or through other files, is an error.
+    pub fn load(
+        sources: &mut SourceMap,
+        document: FileId,
+        library: &[PathBuf],
+    ) -> Result<(Self, Header), Vec<Diagnostic>> {
+        let file = sources.get(document);
+        let dir = Path::new(file.name()).parent().map(Path::to_path_buf);
+
+        let doc = match parse(file)? {
+            ParsedFile::Doc(doc) => doc,
+            ParsedFile::Logic(_) => {
+                let span = Span::from(file.start()..file.end());
+
+                return Err(vec![Diagnostic::error(span, "invalid result type")
+                    .with_note("only documents can be turned into a pdf")]);
+            }
+        };
+
+        let header = Header::eval(doc.header())
+            .map_err(|errors| errors.into_iter().map(Diagnostic::from).collect::<Vec<_>>())?;
+        let includes = include_calls(doc.content().tokens());
+
+        let mut loader = Loader {
+            sources,
+            library,
+            modules: Vec::new(),
+            loaded_modules: BTreeMap::new(),
+            includes: BTreeMap::new(),
+            loaded_includes: BTreeMap::new(),
+            stack: Vec::new(),
+        };
+
+        let imports = loader.imports(&header, dir.as_deref())?;
+        loader.includes(includes, dir.as_deref())?;
+
+        let files = Self {
+            document,
+            modules: loader.modules,
+            imports,
+            includes: loader.includes,
+        };
+
+        Ok((files, header))
+    }
+
+    pub fn document(&self) -> FileId {
+        self.document
+    }
+
+    /// Parses the files again, they were already checked when they were loaded.
+    pub fn parse<'a>(&self, sources: &'a SourceMap) -> Parsed<'a> {
+        let document = match parse(sources.get(self.document)) {
+            Ok(ParsedFile::Doc(doc)) => doc,
+            _ => unreachable!("the document is checked when it is loaded"),
+        };
+
+        let modules = self
+            .modules
+            .iter()
+            .map(|module| match parse(sources.get(module.file)) {
+                Ok(ParsedFile::Logic(logic)) => logic,
+                _ => unreachable!("modules are checked when they are loaded"),
+            })
+            .collect();
+
+        let includes = self
+            .includes
+            .values()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|file| {
+                let tokens = parse_content(sources.get(*file)).unwrap_or_else(|_| {
+                    unreachable!("included files are checked when they are loaded")
+                });
+
+                (*file, tokens)
+            })
+            .collect();
+
+        Parsed {
+            document,
+            modules,
+            includes,
+        }
+    }
+
+    /// Evaluates the modules, and makes the modules and included files of the document available to
+    /// `interpreter`.
+    pub fn prepare<'a>(
+        &self,
+        parsed: &'a Parsed<'a>,
+        interpreter: Interpreter<'a>,
+    ) -> Interpreter<'a> {
+        let includes = self
+            .includes
+            .iter()
+            .map(|(start, file)| (*start, parsed.includes[file].as_slice()))
+            .collect();
+
+        let mut interpreter = interpreter.with_includes(includes);
+        let mut modules: Vec<Module> = Vec::with_capacity(parsed.modules.len());
+
+        for (file, module) in parsed.modules.iter().zip(&self.modules) {
+            let imports = module
+                .imports
+                .iter()
+                .map(|index| modules[*index].clone())
+                .collect::<Vec<_>>();
+
+            modules.push(interpreter.eval_module(file.definitions(), &imports));
+        }
+
+        for index in &self.imports {
+            interpreter.import(&modules[*index]);
+        }
+
+        interpreter
+    }
+}
+
+impl<'a> Parsed<'a> {
+    pub fn document(&self) -> &DocFile<'a> {
+        &self.document
+    }
+}
+
+impl Loader<'_> {
+    /// Loads the modules in the `import` field of `header`, which is a name or a list of names.
+    fn imports(
+        &mut self,
+        header: &Header,
+        dir: Option<&Path>,
+    ) -> Result<Vec<usize>, Vec<Diagnostic>> {
+        let (Some(value), Some(span)) = (header.get("import"), header.span("import")) else {
+            return Ok(Vec::new());
+        };
+
+        let names = match value {
+            Value::List(items) => items.iter().map(module_name).collect::<Option<Vec<_>>>(),
+            value => module_name(value).map(|name| vec![name]),
+        };
+
+        let names = names.ok_or_else(|| {
+            vec![Diagnostic::error(
+                span,
+                "'import' needs to be a module name or a list of module names",
+            )]
+        })?;
+
+        names
+            .into_iter()
+            .map(|name| self.load_module(name, span, dir))
+            .collect()
+    }
+
+    /// Loads a module and the modules it imports, returns its index.
+    fn load_module(
+        &mut self,
+        name: &str,
+        span: Span,
+        dir: Option<&Path>,
+    ) -> Result<usize, Vec<Diagnostic>> {
+        let (location, file_name) = self.resolve(name, dir, true).ok_or_else(|| {
+            vec![
+                Diagnostic::error(span, format!("module '{}' not found", name)).with_note(
+                    "modules are searched next to the importing file and in the directories given \
+                     with '--lib' or in ACRYL_PATH",
+                ),
+            ]
+        })?;
+
+        if let Some(index) = self.loaded_modules.get(&location) {
+            return Ok(*index);
+        }
+
+        self.check_cycle(&location, &file_name, span, "imports")?;
+
+        let file = self.read(&location, &file_name, span)?;
+        let source = self.sources.get(file);
+
+        let logic = match parse(source)? {
+            ParsedFile::Logic(logic) => logic,
+            ParsedFile::Doc(_) => {
+                return Err(vec![Diagnostic::error(
+                    span,
+                    format!("'{}' is a document", file_name),
+                )
+                .with_note("only logic files can be imported")])
+            }
+        };
+
+        let header = Header::eval(logic.header())
+            .map_err(|errors| errors.into_iter().map(Diagnostic::from).collect::<Vec<_>>())?;
+        let includes = include_calls(logic.definitions());
+
+        let dir = file_dir(&location, &file_name);
+
+        self.stack.push((location.clone(), file_name));
+        let imports = self.imports(&header, dir.as_deref())?;
+        self.includes(includes, dir.as_deref())?;
+        self.stack.pop();
+
+        let index = self.modules.len();
+        self.modules.push(ModuleFile { file, imports });
+        self.loaded_modules.insert(location, index);
+
+        Ok(index)
+    }
+
+    /// Loads the files of `\include` calls and the files they include.
+    fn includes(
+        &mut self,
+        includes: Vec<Include>,
+        dir: Option<&Path>,
+    ) -> Result<(), Vec<Diagnostic>> {
+        for include in includes {
+            let file = self.load_include(&include, dir)?;
+            self.includes.insert(include.span.start, file);
+        }
+
+        Ok(())
+    }
+
+    fn load_include(
+        &mut self,
+        include: &Include,
+        dir: Option<&Path>,
+    ) -> Result<FileId, Vec<Diagnostic>> {
+        let (location, file_name) = self.resolve(&include.path, dir, false).ok_or_else(|| {
+            vec![
+                Diagnostic::error(include.span, format!("file '{}' not found", include.path))
+                    .with_note("included files are searched next to the including file"),
+            ]
+        })?;
+
+        if let Some(file) = self.loaded_includes.get(&location) {
+            return Ok(*file);
+        }
+
+        self.check_cycle(&location, &file_name, include.span, "includes")?;
+
+        let file = self.read(&location, &file_name, include.span)?;
+        let includes = include_calls(&parse_content(self.sources.get(file))?);
+
+        let dir = file_dir(&location, &file_name);
+
+        self.stack.push((location.clone(), file_name));
+        self.includes(includes, dir.as_deref())?;
+        self.stack.pop();
+
+        self.loaded_includes.insert(location, file);
+
+        Ok(file)
+    }
+
+    /// Reports a file that is loaded again while it is still being loaded.
+    fn check_cycle(
+        &self,
+        location: &Location,
+        file_name: &str,
+        span: Span,
+        verb: &str,
+    ) -> Result<(), Vec<Diagnostic>> {
+        let Some(start) = self.stack.iter().position(|(other, _)| other == location) else {
+            return Ok(());
+        };
+
+        let cycle = self.stack[start..]
+            .iter()
+            .map(|(_, name)| name.as_str())
+            .chain([file_name])
+            .collect::<Vec<_>>();
+
+        Err(vec![Diagnostic::error(
+            span,
+            format!("'{}' {} itself", file_name, verb),
+        )
+        .with_note(format!("cycle: {}", cycle.join(" -> ")))])
+    }
+
+    /// Adds the source of a file to the source map.
+    fn read(
+        &mut self,
+        location: &Location,
+        file_name: &str,
+        span: Span,
+    ) -> Result<FileId, Vec<Diagnostic>> {
+        let source = match location {
+            Location::File(path) => fs::read_to_string(path).map_err(|error| {
+                vec![Diagnostic::error(
+                    span,
+                    format!("could not read '{}': {}", file_name, error),
+                )]
+            })?,
+            Location::Std => STD.to_owned(),
+        };
+
+        Ok(self.sources.add(file_name, source))
+    }
+
+    /// Finds a file and the name it is shown with, `.acryl` may be left out.
+    ///
+    /// Only modules are searched in the library path, and only they can be the standard library.
+    fn resolve(&self, name: &str, dir: Option<&Path>, module: bool) -> Option<(Location, String)> {
+        let file = Path::new(name);
+        let file = match file.extension() {
+            Some(_) => file.to_path_buf(),
+            None => file.with_extension(EXTENSION),
+        };
+
+        let library = if module { self.library } else { &[] };
+
+        let found = dir
+            .into_iter()
+            .chain(library.iter().map(PathBuf::as_path))
+            .map(|dir| dir.join(&file))
+            .find(|path| path.is_file());
+
+        match found {
+            Some(path) => {
+                let location = Location::File(path.canonicalize().unwrap_or_else(|_| path.clone()));
+                Some((location, path.display().to_string()))
+            }
+            None if module && name == STD_NAME => Some((Location::Std, STD_NAME.to_owned())),
+            None => None,
+        }
+    }
+}
+
+/// The directory relative imports of a file start in, `file_name` is the path it was found at,
+/// which keeps the paths in messages short.
+fn file_dir(location: &Location, file_name: &str) -> Option<PathBuf> {
+    match location {
+        Location::File(_) => Path::new(file_name).parent().map(Path::to_path_buf),
+        Location::Std => None,
+    }
+}
+
+/// Finds the `\include` calls in content and in the bodies of definitions and control flow, calls
+/// without a string as their path are reported by the interpreter.
+fn include_calls(tokens: &[ContentToken]) -> Vec<Include> {
+    let mut includes = Vec::new();
+
+    for token in tokens {
+        match token {
+            ContentToken::Fn {
+                name,
+                arguments,
+                content,
+                span,
+                ..
+            } => {
+                if *name == Interpreter::INCLUDE {
+                    if let Some((Expr::Token(CodeToken::Str(path)), _)) =
+                        Arguments::new(arguments).get(0, "path")
+                    {
+                        includes.push(Include {
+                            path: path.clone(),
+                            span: *span,
+                        });
+                    }
+                }
+
+                includes.extend(include_calls(content));
+            }
+            ContentToken::Def { body, .. } | ContentToken::For { body, .. } => {
+                includes.extend(include_calls(body))
+            }
+            ContentToken::If {
+                then, otherwise, ..
+            } => {
+                includes.extend(include_calls(then));
+                includes.extend(include_calls(otherwise));
+            }
+            _ => {}
+        }
+    }
+
+    includes
+}
+
+/// Modules are named by an identifier like `std` or by a string with a path.
+fn module_name(value: &Value) -> Option<&str> {
+    match value {
+        Value::Ident(name) | Value::Str(name) => Some(name),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env, fs,
+        path::{Path, PathBuf},
+        process,
+    };
+
+    use acryl_interpreter::Interpreter;
+    use acryl_layout::{node::Node, FONT_DEJAVU_SERIF};
+    use acryl_parser::{diagnostic::Diagnostic, source_map::SourceMap};
+    use acryl_pdf::{font::Font, resource_manager::ResourceManager};
+
+    use super::Files;
+
+    /// Writes `files` into a new directory for `test`, the first one is the document.
+    fn write(test: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = env::temp_dir().join(format!("acryl-{}-{}", test, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        for (name, source) in files {
+            let path = dir.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, source).unwrap();
+        }
+
+        dir
+    }
+
+    /// Loads the document of a directory from [`write`], `library` is relative to it.
+    fn load(
+        dir: &Path,
+        document: &str,
+        library: &[&str],
+    ) -> (SourceMap, Result<Files, Vec<Diagnostic>>) {
+        let mut sources = SourceMap::new();
+        let path = dir.join(document);
+        let source = fs::read_to_string(&path).unwrap();
+        let document = sources.add(path.display().to_string(), source);
+        let library = library.iter().map(|name| dir.join(name)).collect::<Vec<_>>();
+
+        let files = Files::load(&mut sources, document, &library).map(|(files, _)| files);
+
+        (sources, files)
+    }
+
+    /// The names of the loaded modules, in the order they are evaluated.
+    fn module_names(sources: &SourceMap, files: &Files) -> Vec<String> {
+        files
+            .modules
+            .iter()
+            .map(|module| sources.get(module.file).name().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn modules_are_found_next_to_the_importing_file() {
+        let dir = write(
+            "next-to",
+            &[
+                ("doc.acryl", "doc { import: a }"),
+                ("a.acryl", "logic { import: \"sub/b\" }"),
+                ("sub/b.acryl", "logic { import: c }"),
+                ("sub/c.acryl", "logic {}"),
+            ],
+        );
+        let (sources, files) = load(&dir, "doc.acryl", &[]);
+        let files = files.unwrap_or_else(|errors| panic!("{:?}", errors));
+
+        let path = |name: &str| dir.join(name).display().to_string();
+        assert_eq!(
+            module_names(&sources, &files),
+            [path("sub/c.acryl"), path("sub/b.acryl"), path("a.acryl")]
+        );
+        assert_eq!(files.imports, [2]);
+        assert_eq!(files.modules[2].imports, [1]);
+        assert_eq!(files.modules[1].imports, [0]);
+    }
+
+    #[test]
+    fn the_library_is_searched_after_the_importing_file() {
+        let dir = write(
+            "library",
+            &[
+                ("doc.acryl", "doc { import: [a, b] }"),
+                ("a.acryl", "logic {}"),
+                ("lib/a.acryl", "logic {}"),
+                ("lib/b.acryl", "logic {}"),
+            ],
+        );
+        let (sources, files) = load(&dir, "doc.acryl", &["lib"]);
+        let files = files.unwrap_or_else(|errors| panic!("{:?}", errors));
+
+        let path = |name: &str| dir.join(name).display().to_string();
+        assert_eq!(
+            module_names(&sources, &files),
+            [path("a.acryl"), path("lib/b.acryl")]
+        );
+    }
+
+    #[test]
+    fn std_is_built_in_unless_a_file_replaces_it() {
+        let dir = write("std", &[("doc.acryl", "doc { import: std }")]);
+        let (sources, files) = load(&dir, "doc.acryl", &[]);
+        assert_eq!(module_names(&sources, &files.unwrap()), ["std"]);
+
+        let dir = write(
+            "own-std",
+            &[("doc.acryl", "doc { import: std }"), ("std.acryl", "logic {}")],
+        );
+        let (sources, files) = load(&dir, "doc.acryl", &[]);
+        assert_eq!(
+            module_names(&sources, &files.unwrap()),
+            [dir.join("std.acryl").display().to_string()]
+        );
+    }
+
+    #[test]
+    fn modules_are_loaded_once() {
+        let dir = write(
+            "once",
+            &[
+                ("doc.acryl", "doc { import: [a, b] }"),
+                ("a.acryl", "logic { import: b }"),
+                ("b.acryl", "logic {}"),
+            ],
+        );
+        let (sources, files) = load(&dir, "doc.acryl", &[]);
+        let files = files.unwrap();
+
+        assert_eq!(module_names(&sources, &files).len(), 2);
+        assert_eq!(files.imports, [1, 0]);
+    }
+
+    #[test]
+    fn import_cycles_are_errors() {
+        let dir = write(
+            "cycle",
+            &[
+                ("doc.acryl", "doc { import: a }"),
+                ("a.acryl", "logic { import: b }"),
+                ("b.acryl", "logic { import: a }"),
+            ],
+        );
+        let (_, files) = load(&dir, "doc.acryl", &[]);
+        let errors = files.err().unwrap();
+
+        let path = |name: &str| dir.join(name).display().to_string();
+        assert_eq!(errors[0].message, format!("'{}' imports itself", path("a.acryl")));
+        assert_eq!(
+            errors[0].notes,
+            [format!(
+                "cycle: {} -> {} -> {}",
+                path("a.acryl"),
+                path("b.acryl"),
+                path("a.acryl")
+            )]
+        );
+    }
+
+    #[test]
+    fn missing_modules_and_documents_can_not_be_imported() {
+        let dir = write(
+            "invalid",
+            &[
+                ("doc.acryl", "doc { import: missing }"),
+                ("other.acryl", "doc { import: doc }"),
+            ],
+        );
+
+        let (_, files) = load(&dir, "doc.acryl", &[]);
+        assert_eq!(files.err().unwrap()[0].message, "module 'missing' not found");
+
+        let (_, files) = load(&dir, "other.acryl", &[]);
+        assert_eq!(
+            files.err().unwrap()[0].message,
+            format!("'{}' is a document", dir.join("doc.acryl").display())
+        );
+    }
+
+    #[test]
+    fn includes_are_relative_to_the_including_file() {
+        let dir = write(
+            "include",
+            &[
+                ("doc.acryl", "doc {}\n\\include(\"parts/a.acryl\")\n\\include(\"parts/a\")"),
+                ("parts/a.acryl", "\\include(\"b.acryl\")"),
+                ("parts/b.acryl", "text"),
+            ],
+        );
+        let (sources, files) = load(&dir, "doc.acryl", &[]);
+        let files = files.unwrap_or_else(|errors| panic!("{:?}", errors));
+
+        // Both calls of the document include the same file, which is loaded once, the call in
+        // "a.acryl" comes last since that file comes after the document in the source map
+        let names = files
+            .includes
+            .values()
+            .map(|file| sources.get(*file).name().to_string())
+            .collect::<Vec<_>>();
+        let path = |name: &str| dir.join(name).display().to_string();
+        assert_eq!(
+            names,
+            [path("parts/a.acryl"), path("parts/a.acryl"), path("parts/b.acryl")]
+        );
+        assert_eq!(sources.files().count(), 3);
+    }
+
+    #[test]
+    fn included_content_is_part_of_the_document() {
+        let dir = write(
+            "include-eval",
+            &[
+                ("doc.acryl", "doc {}\nbefore \\include(\"part\") after"),
+                ("part.acryl", "\\let x = \"included\"\n\\(x) text"),
+            ],
+        );
+        let (sources, files) = load(&dir, "doc.acryl", &[]);
+        let files = files.unwrap();
+        let parsed = files.parse(&sources);
+
+        let font = ResourceManager::new().add_font(Font::load(FONT_DEJAVU_SERIF).unwrap());
+        let interpreter = files.prepare(&parsed, Interpreter::new(font));
+        let text = match interpreter.eval(parsed.document().content().tokens()) {
+            Ok(evaluated) => evaluated.nodes.iter().map(Node::plain_text).collect::<Vec<_>>(),
+            Err(_) => panic!("the document does not evaluate"),
+        };
+
+        assert_eq!(text, ["before included text after"]);
+    }
+
+    #[test]
+    fn include_cycles_and_missing_files_are_errors() {
+        let dir = write(
+            "include-cycle",
+            &[
+                ("doc.acryl", "doc {}\n\\include(\"a\")"),
+                ("a.acryl", "\\include(\"b\")"),
+                ("b.acryl", "\\if(true) { \\include(\"a\") }"),
+                ("missing.acryl", "doc {}\n\\include(\"c\")"),
+            ],
+        );
+
+        let (_, files) = load(&dir, "doc.acryl", &[]);
+        let errors = files.err().unwrap();
+        let path = |name: &str| dir.join(name).display().to_string();
+        assert_eq!(errors[0].message, format!("'{}' includes itself", path("a.acryl")));
+
+        let (_, files) = load(&dir, "missing.acryl", &[]);
+        assert_eq!(files.err().unwrap()[0].message, "file 'c' not found");
+    }
+}
@@ -1 +1,2 @@
+pub mod page_builder;
 pub mod page_size;
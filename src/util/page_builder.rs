@@ -0,0 +1,38 @@
+use std::str::FromStr;
+
+use acryl_core::math::{Mm, Pt};
+use acryl_layout::padding_values::PaddingValues;
+
+/// How the content is placed on a page.
+#[derive(Default)]
+pub enum PageBuilder {
+    /// The content starts at the edge of the page.
+    Plain,
+    /// The content has a margin to the edges of the page.
+    #[default]
+    Padded,
+}
+
+#[derive(Debug)]
+pub struct PageBuilderUnknown;
+
+impl FromStr for PageBuilder {
+    type Err = PageBuilderUnknown;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Plain" => Ok(Self::Plain),
+            "Padded" => Ok(Self::Padded),
+            _ => Err(PageBuilderUnknown),
+        }
+    }
+}
+
+impl PageBuilder {
+    pub fn get_margin(&self) -> PaddingValues<Pt> {
+        match self {
+            PageBuilder::Plain => PaddingValues::all(Pt(0.0)),
+            PageBuilder::Padded => PaddingValues::all(Mm(25.0).into()),
+        }
+    }
+}